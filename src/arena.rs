@@ -0,0 +1,233 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::iter::Extend;
+use std::slice;
+
+//Stays valid for as long as the entity it was issued for, a removed entity's id never finds the next one in its slot
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct EntityId {
+    index: u32,
+    generation: u32
+}
+
+impl EntityId {
+    //Slot the id was issued from, shared with whatever takes the slot once the entity is gone
+    pub fn get_index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+struct Slot {
+    generation: u32,
+    entry: Option<usize>
+}
+
+//Entities are kept packed in insertion order, the same order the Vecs this replaced iterated in,
+//with slots mapping ids to where their entity currently sits
+pub struct Arena<T> {
+    entries: Vec<(EntityId, T)>,
+    slots: Vec<Slot>,
+    free_slots: Vec<u32>
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            entries: vec![],
+            slots: vec![],
+            free_slots: vec![]
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> EntityId {
+        let index = match self.free_slots.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot { generation: 0, entry: None });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.entry = Some(self.entries.len());
+        let id = EntityId {
+            index,
+            generation: slot.generation
+        };
+        self.entries.push((id, value));
+        id
+    }
+
+    fn get_entry(&self, id: EntityId) -> Option<usize> {
+        match self.slots.get(id.index as usize) {
+            Some(slot) if slot.generation == id.generation => slot.entry,
+            _ => None
+        }
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&T> {
+        match self.get_entry(id) {
+            Some(entry) => Some(&self.entries[entry].1),
+            None => None
+        }
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut T> {
+        match self.get_entry(id) {
+            Some(entry) => Some(&mut self.entries[entry].1),
+            None => None
+        }
+    }
+
+    //Drops every entity the predicate rejects, keeping the rest in order
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        {
+            let slots = &mut self.slots;
+            let free_slots = &mut self.free_slots;
+            self.entries.retain(|&(id, ref value)| {
+                let kept = keep(value);
+                if !kept {
+                    let slot = &mut slots[id.index as usize];
+                    slot.generation = slot.generation.wrapping_add(1);
+                    slot.entry = None;
+                    free_slots.push(id.index);
+                }
+                kept
+            });
+        }
+        for (i, &(id, _)) in self.entries.iter().enumerate() {
+            self.slots[id.index as usize].entry = Some(i);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter<'a>(&'a self) -> slice::Iter<'a, (EntityId, T)> {
+        self.entries.iter()
+    }
+
+    pub fn values<'a>(&'a self) -> Values<'a, T> {
+        Values {
+            entries: self.entries.iter()
+        }
+    }
+
+    pub fn values_mut<'a>(&'a mut self) -> ValuesMut<'a, T> {
+        ValuesMut {
+            entries: self.entries.iter_mut()
+        }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Arena<T> {
+        Arena::new()
+    }
+}
+
+impl<T> Extend<T> for Arena<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+pub struct Values<'a, T: 'a> {
+    entries: slice::Iter<'a, (EntityId, T)>
+}
+
+impl<'a, T> Iterator for Values<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.entries.next().map(|entry| &entry.1)
+    }
+}
+
+pub struct ValuesMut<'a, T: 'a> {
+    entries: slice::IterMut<'a, (EntityId, T)>
+}
+
+impl<'a, T> Iterator for ValuesMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.entries.next().map(|entry| &mut entry.1)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Arena<T> {
+    type Item = &'a T;
+    type IntoIter = Values<'a, T>;
+
+    fn into_iter(self) -> Values<'a, T> {
+        self.values()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Arena<T> {
+    type Item = &'a mut T;
+    type IntoIter = ValuesMut<'a, T>;
+
+    fn into_iter(self) -> ValuesMut<'a, T> {
+        self.values_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_arena(values: &[u32]) -> (Arena<u32>, Vec<EntityId>) {
+        let mut arena = Arena::new();
+        let ids = values.iter().map(|value| arena.insert(*value)).collect();
+        (arena, ids)
+    }
+
+    #[test]
+    fn removed_id_misses_the_entity_reusing_its_slot() {
+        let (mut arena, ids) = create_arena(&[1, 2, 3]);
+        arena.retain(|value| *value != 2);
+        assert_eq!(arena.get(ids[1]), None);
+        let reused = arena.insert(4);
+        assert_eq!(reused.get_index(), ids[1].get_index());
+        assert_eq!(arena.get(ids[1]), None);
+        assert_eq!(arena.get(reused), Some(&4));
+    }
+
+    #[test]
+    fn order_is_kept_across_retain_and_insert() {
+        let (mut arena, _) = create_arena(&[1, 2, 3, 4, 5]);
+        arena.retain(|value| value % 2 == 1);
+        arena.insert(6);
+        arena.insert(7);
+        assert_eq!(arena.values().cloned().collect::<Vec<u32>>(), vec![1, 3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn ids_and_values_stay_aligned_after_removals() {
+        let (mut arena, ids) = create_arena(&[10, 20, 30, 40]);
+        arena.retain(|value| *value != 10 && *value != 30);
+        let added = arena.insert(50);
+        assert_eq!(arena.iter().map(|&(id, _)| id).collect::<Vec<EntityId>>(), vec![ids[1], ids[3], added]);
+        for &(id, ref value) in arena.iter() {
+            assert_eq!(arena.get(id), Some(value));
+        }
+    }
+
+    #[test]
+    fn get_mut_finds_entries_moved_by_compaction() {
+        let (mut arena, ids) = create_arena(&[1, 2, 3]);
+        arena.retain(|value| *value != 1);
+        if let Some(value) = arena.get_mut(ids[2]) {
+            *value = 33;
+        }
+        assert_eq!(arena.get(ids[2]), Some(&33));
+        assert_eq!(arena.values().cloned().collect::<Vec<u32>>(), vec![2, 33]);
+    }
+}
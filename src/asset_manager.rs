@@ -12,8 +12,12 @@ pub struct AssetManager {
     window_h: u32,
     pub large_splash_font: graphics::Font,
     pub med_splash_font: graphics::Font,
+    pub small_font: graphics::Font,
     pub player: graphics::Image,
     pub drone1: graphics::Image,
+    pub tank1: graphics::Image,
+    pub kamikaze1: graphics::Image,
+    pub splitter_fragment1: graphics::Image,
     pub projectile1: graphics::Image,
     pub explosion1: graphics::Image
 }
@@ -25,27 +29,61 @@ impl AssetManager {
             window_h,
             large_splash_font: graphics::Font::new(ctx, "/fonts/OpenSans-ExtraBold.ttf", 48).unwrap(),
             med_splash_font: graphics::Font::new(ctx, "/fonts/OpenSans-Bold.ttf", 24).unwrap(),
+            small_font: graphics::Font::new(ctx, "/fonts/OpenSans-Bold.ttf", 14).unwrap(),
             player: graphics::Image::new(ctx, "/playerFighter.png").unwrap(),
             drone1: graphics::Image::new(ctx, "/drone1.png").unwrap(),
+            tank1: graphics::Image::new(ctx, "/tank1.png").unwrap(),
+            kamikaze1: graphics::Image::new(ctx, "/kamikaze1.png").unwrap(),
+            splitter_fragment1: graphics::Image::new(ctx, "/splitterFragment1.png").unwrap(),
             projectile1: graphics::Image::new(ctx, "/projectile1.png").unwrap(),
             explosion1: graphics::Image::new(ctx, "/explosion1.png").unwrap()
         })
     }
 
-    pub fn get_asset(&self, asset_key: String) -> &graphics::Image {
-        match asset_key.as_str() {
+    pub fn get_asset(&self, asset_key: &str) -> &graphics::Image {
+        match asset_key {
             "player" => &self.player,
-            "drone1" => &self.drone1,
-            "projectile1" => &self.projectile1,
-            "explosion1" => &self.explosion1,
+            "drone1" | "drone_gunner" | "drone_spread" | "drone_weaver" |
+            "drone_splitter" | "boss_core" | "boss_turret" => &self.drone1,
+            "tank" => &self.tank1,
+            "kamikaze" => &self.kamikaze1,
+            "splitter_fragment" => &self.splitter_fragment1,
+            "projectile1" | "pickup_weapon" | "pickup_shield" | "pickup_life" |
+            "pickup_bomb" | "pickup_speed" | "laser" | "missile" => &self.projectile1,
+            "explosion1" | "bomb_flash" => &self.explosion1,
             _ => &self.projectile1
         }
     }
 
-    pub fn draw_asset(&self, asset_key: String, ctx: &mut Context, draw_param: graphics::DrawParam) {
+    pub fn get_asset_tint(&self, asset_key: &str) -> Option<graphics::Color> {
+        match asset_key {
+            "drone_gunner" => Some(graphics::Color::new(1.0, 0.55, 0.55, 1.0)),
+            "drone_spread" => Some(graphics::Color::new(1.0, 0.85, 0.4, 1.0)),
+            "drone_weaver" => Some(graphics::Color::new(0.5, 1.0, 0.6, 1.0)),
+            "kamikaze" => Some(graphics::Color::new(1.0, 0.35, 0.2, 1.0)),
+            "tank" => Some(graphics::Color::new(0.6, 0.6, 0.75, 1.0)),
+            "drone_splitter" | "splitter_fragment" => Some(graphics::Color::new(0.75, 0.5, 1.0, 1.0)),
+            "boss_core" => Some(graphics::Color::new(0.9, 0.3, 0.6, 1.0)),
+            "boss_turret" => Some(graphics::Color::new(0.7, 0.4, 0.8, 1.0)),
+            "bomb_flash" => Some(graphics::Color::new(1.0, 1.0, 0.9, 0.8)),
+            "laser" => Some(graphics::Color::new(0.4, 1.0, 1.0, 1.0)),
+            "missile" => Some(graphics::Color::new(1.0, 0.7, 0.3, 1.0)),
+            "pickup_weapon" => Some(graphics::Color::new(1.0, 0.6, 0.1, 1.0)),
+            "pickup_shield" => Some(graphics::Color::new(0.3, 0.7, 1.0, 1.0)),
+            "pickup_life" => Some(graphics::Color::new(0.3, 1.0, 0.3, 1.0)),
+            "pickup_bomb" => Some(graphics::Color::new(1.0, 0.2, 0.2, 1.0)),
+            "pickup_speed" => Some(graphics::Color::new(1.0, 1.0, 0.3, 1.0)),
+            _ => None
+        }
+    }
+
+    pub fn draw_asset(&self, asset_key: String, ctx: &mut Context, mut draw_param: graphics::DrawParam) {
+        if draw_param.color.is_none() {
+            draw_param.color = self.get_asset_tint(&asset_key);
+        }
         graphics::draw_ex(
             ctx,
-            self.get_asset(asset_key),
+            self.get_asset(&asset_key),
             draw_param
         ).unwrap();
     }
@@ -74,14 +112,35 @@ impl AssetManager {
         ))
     }
 
+    pub fn draw_text_centered_at(&self, ctx: &mut Context, text: graphics::Text, pos: Point2<f32>) {
+        AssetManager::draw_anchored_text(ctx, &text, Point2::new(
+            pos.x - (text.width() / 2) as f32,
+            pos.y - (text.height() / 2) as f32
+        ))
+    }
+
     pub fn draw_top_left_text(&self, ctx: &mut Context, text: graphics::Text) {
         AssetManager::draw_anchored_text(ctx, &text, Point2::new(5.0, 2.0))
     }
 
+    pub fn draw_bottom_left_text(&self, ctx: &mut Context, text: graphics::Text) {
+        AssetManager::draw_anchored_text(ctx, &text, Point2::new(
+            5.0,
+            self.window_h as f32 - (text.height() as f32) - 10.0
+        ))
+    }
+
     pub fn draw_top_right_text(&self, ctx: &mut Context, text: graphics::Text) {
         AssetManager::draw_anchored_text(ctx, &text, Point2::new(
             self.window_w as f32 - (text.width() as f32) - 5.0,
             2.0
         ))
     }
-}
\ No newline at end of file
+
+    pub fn draw_bottom_right_text(&self, ctx: &mut Context, text: graphics::Text) {
+        AssetManager::draw_anchored_text(ctx, &text, Point2::new(
+            self.window_w as f32 - (text.width() as f32) - 5.0,
+            self.window_h as f32 - (text.height() as f32) - 10.0
+        ))
+    }
+}
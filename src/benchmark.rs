@@ -0,0 +1,107 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng, XorShiftRng};
+use ncollide::bounding_volume::BoundingVolume;
+use super::enemy::*;
+use super::projectile::*;
+use super::play_space::*;
+use super::entity_manager::*;
+use super::game_state::GameState;
+use super::collision_shape::SpriteHulls;
+
+const PROJECTILE_COUNTS: [usize; 5] = [500, 1000, 2000, 4000, 8000];
+const ENEMY_COUNT: usize = 200;
+const SAMPLES: u32 = 20;
+const BENCHMARK_SEED: u32 = 1;
+
+fn create_crowded_state(play_space: &PlaySpace, sprite_hulls: &Rc<SpriteHulls>, projectile_count: usize, rng: &mut XorShiftRng) -> GameState {
+    let mut game_state = GameState::new(play_space.clone(), BENCHMARK_SEED, 1, sprite_hulls.clone());
+    let area = play_space.player_area;
+    for _ in 0..ENEMY_COUNT {
+        EntityManager::add_enemy(&mut game_state, Enemy::new_tank(rng.gen_range(0.0, area.w), rng.gen_range(0.0, area.h)));
+    }
+    for _ in 0..projectile_count {
+        game_state.projectiles.insert(Projectile::new_player(rng.gen_range(0.0, area.w), rng.gen_range(0.0, area.h), 0.0));
+    }
+    game_state
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1_000_000.0
+}
+
+//The all-pairs test the grid replaced, kept here as the baseline to compare against
+fn time_all_pairs(game_state: &GameState) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    let enemy_areas: Vec<_> = game_state.enemies.values()
+        .filter_map(|enemy| EntityManager::create_entity_collision_area(&game_state.sprite_hulls, enemy))
+        .collect();
+    for projectile in &game_state.projectiles {
+        if let Some(projectile_area) = EntityManager::create_entity_collision_area(&game_state.sprite_hulls, projectile) {
+            hits += enemy_areas.iter().filter(|enemy_area| projectile_area.intersects(enemy_area)).count();
+        }
+    }
+    (start.elapsed(), hits)
+}
+
+//Same pair search through the grid, starting from an empty grid so every enemy is placed as it would be on
+//the tick a wave arrives, the most a tick's grid update can cost
+fn time_grid_pairs(game_state: &mut GameState) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    {
+        let sprite_hulls = &game_state.sprite_hulls;
+        game_state.enemy_grid.update(game_state.enemies.iter()
+            .map(|&(id, ref enemy)| (id, EntityManager::create_entity_collision_area(sprite_hulls, enemy))));
+    }
+    let mut nearby_enemies = vec![];
+    for projectile in &game_state.projectiles {
+        if let Some(projectile_area) = EntityManager::create_entity_collision_area(&game_state.sprite_hulls, projectile) {
+            game_state.enemy_grid.query(&projectile_area, &mut nearby_enemies);
+            hits += nearby_enemies.len();
+        }
+    }
+    (start.elapsed(), hits)
+}
+
+pub fn run_collision_benchmark(play_space_w: f32, play_space_h: f32, sprite_hulls: Rc<SpriteHulls>) {
+    let play_space = PlaySpace::new(play_space_w, play_space_h);
+    let mut rng = XorShiftRng::from_seed([BENCHMARK_SEED, 0x9E37_79B9, 0x85EB_CA6B, 0x2545_F491]);
+    println!("{} enemies, average of {} samples", ENEMY_COUNT, SAMPLES);
+    for &projectile_count in PROJECTILE_COUNTS.iter() {
+        let mut grid_total = Duration::new(0, 0);
+        let mut all_pairs_total = Duration::new(0, 0);
+        let mut resolution_total = Duration::new(0, 0);
+        let mut hits_total = 0;
+        let mut mismatched_samples = 0;
+        for _ in 0..SAMPLES {
+            let mut game_state = create_crowded_state(&play_space, &sprite_hulls, projectile_count, &mut rng);
+            let (all_pairs_time, all_pairs_hits) = time_all_pairs(&game_state);
+            let (grid_time, grid_hits) = time_grid_pairs(&mut game_state);
+            all_pairs_total += all_pairs_time;
+            grid_total += grid_time;
+            hits_total += all_pairs_hits;
+            if grid_hits != all_pairs_hits {
+                mismatched_samples += 1;
+            }
+            let start = Instant::now();
+            EntityManager::collision_resolution(&mut game_state);
+            resolution_total += start.elapsed();
+        }
+        println!("projectiles: {:>5}  grid: {:>8.3} ms  all pairs: {:>8.3} ms  full resolution: {:>8.3} ms  hits: {:>7}",
+            projectile_count,
+            to_ms(grid_total) / SAMPLES as f64,
+            to_ms(all_pairs_total) / SAMPLES as f64,
+            to_ms(resolution_total) / SAMPLES as f64,
+            hits_total / SAMPLES as usize);
+        if mismatched_samples > 0 {
+            println!("  grid and all pairs found different hits in {} of {} samples", mismatched_samples, SAMPLES);
+        }
+    }
+}
@@ -3,8 +3,10 @@ This work is available under the "MIT License”.
 Please see the file LICENSE in this distribution
 for license terms. */
 
+use std::rc::Rc;
 use nalgebra::Vector2;
 use super::camera::*;
+use super::collision_shape::*;
 
 pub struct Body {
     pub pos: Vector2<f32>, //x, y
@@ -12,7 +14,8 @@ pub struct Body {
     pub scale: Vector2<f32>, //width, height
     pub velocity: Vector2<f32>, //speed, angle
     pub rotation: f32,
-    pub collidable: bool
+    pub collidable: bool,
+    pub shape: Rc<CollisionShape>
 }
 
 impl Body {
@@ -31,10 +34,15 @@ impl Body {
             scale: Vector2::new(scale_x, scale_y),
             collidable,
             velocity: Vector2::new(0.0, 0.0),
-            rotation
+            rotation,
+            shape: Rc::new(CollisionShape::rectangle(width / 2.0, height / 2.0))
         }
     }
 
+    pub fn set_shape(&mut self, shape: CollisionShape) {
+        self.shape = Rc::new(shape);
+    }
+
     pub fn get_movement_vector(&self) -> Vector2<f32> {
         Vector2::new(
             self.velocity[1].cos()*self.velocity[0],
@@ -56,13 +64,6 @@ impl Body {
         self.pos.x += movement_vector[0];
         self.pos.y += movement_vector[1];
     }
-
-    pub fn get_scaled_size(&self) -> Vector2<f32> {
-        Vector2::new(
-            self.size[0] * self.scale.x,
-            self.size[1] * self.scale.y
-        )
-    }
 }
 
 impl Clone for Body {
@@ -73,7 +74,8 @@ impl Clone for Body {
             scale: Vector2::new(self.scale.x, self.scale.y),
             collidable: self.collidable,
             velocity: Vector2::new(self.velocity.x, self.velocity.y),
-            rotation: self.rotation
+            rotation: self.rotation,
+            shape: self.shape.clone()
         }
     }
 }
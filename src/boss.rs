@@ -0,0 +1,261 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::f32;
+use nalgebra::Vector2;
+use rand::{Rng, XorShiftRng};
+use super::entity::*;
+use super::body::*;
+use super::collision_shape::*;
+use super::unit::*;
+use super::projectile::*;
+use super::particals::*;
+use super::arena::Arena;
+use super::enemy_weapon::*;
+
+const BOSS_ROTATION: f32 = (f32::consts::PI*3.0)/2.0;
+const BOSS_ENTRY_SPEED: f32 = 2.0;
+const BOSS_SWAY_AMPLITUDE: f32 = 120.0;
+const BOSS_SWAY_FREQUENCY: [f32; 3] = [0.015, 0.022, 0.032];
+const BOSS_PHASE_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+const CORE_HIT_POINTS: u32 = 40;
+const TURRET_HIT_POINTS: u32 = 12;
+const TURRET_OFFSET_Y: f32 = 95.0;
+const CORE_SCORE: u32 = 5000;
+const TURRET_SCORE: u32 = 750;
+const DEFEAT_TICKS: u32 = 150;
+const DEFEAT_EXPLOSION_INTERVAL: u32 = 10;
+const DEFEAT_EXPLOSION_SPREAD: f32 = 140.0;
+
+pub struct BossPart {
+    unit: Unit,
+    offset: Vector2<f32>,
+    is_core: bool,
+    score_value: u32,
+    phase_weapons: Vec<EnemyWeapon>
+}
+
+impl BossPart {
+    fn new(pos: Vector2<f32>, offset: Vector2<f32>, asset_key: &str, scale: f32, shape: CollisionShape, hit_points: u32, is_core: bool, score_value: u32, phase_weapons: Vec<EnemyWeapon>) -> BossPart {
+        let mut body = Body::new(pos.x + offset.x, pos.y + offset.y, 132.0, 128.0, scale, scale, BOSS_ROTATION, true);
+        body.set_shape(shape);
+        let mut unit = Unit::new(body, asset_key.to_string(), 1, 1, true);
+        unit.set_max_hit_points(hit_points);
+        BossPart {
+            unit,
+            offset,
+            is_core,
+            score_value,
+            phase_weapons
+        }
+    }
+
+    fn new_core(pos: Vector2<f32>, strength: f32) -> BossPart {
+        //Hull of the drone body with a bar across its wings
+        let shape = CollisionShape::Compound(vec![
+            (Vector2::new(0.0, 0.0), 0.0, CollisionShape::Capsule(20.0, 48.0)),
+            (Vector2::new(0.0, 10.0), f32::consts::PI / 2.0, CollisionShape::Capsule(40.0, 16.0))
+        ]);
+        BossPart::new(pos, Vector2::new(0.0, 0.0), "boss_core", 1.4, shape,
+            (CORE_HIT_POINTS as f32 * strength) as u32, true, CORE_SCORE,
+            vec![
+                EnemyWeapon::new(2500, AimMode::Straight, ProjectilePattern { count: 3, speed: 7.0, damage: 1 }),
+                EnemyWeapon::new(1500, AimMode::AtPlayer, ProjectilePattern { count: 2, speed: 8.0, damage: 1 }),
+                EnemyWeapon::new(1200, AimMode::Spread(f32::consts::PI / 2.0), ProjectilePattern { count: 7, speed: 6.0, damage: 1 })
+            ]
+        )
+    }
+
+    fn new_turret(pos: Vector2<f32>, offset_y: f32, strength: f32) -> BossPart {
+        BossPart::new(pos, Vector2::new(-20.0, offset_y), "boss_turret", 0.6, CollisionShape::Circle(48.0),
+            (TURRET_HIT_POINTS as f32 * strength) as u32, false, TURRET_SCORE,
+            vec![
+                EnemyWeapon::new(1500, AimMode::AtPlayer, ProjectilePattern { count: 1, speed: 8.0, damage: 1 }),
+                EnemyWeapon::new(1800, AimMode::Spread(f32::consts::PI / 4.0), ProjectilePattern { count: 3, speed: 7.0, damage: 1 }),
+                EnemyWeapon::new(1400, AimMode::Spread(f32::consts::PI / 3.0), ProjectilePattern { count: 5, speed: 8.0, damage: 1 })
+            ]
+        )
+    }
+
+    pub fn is_core(&self) -> bool {
+        self.is_core
+    }
+
+    pub fn get_score_value(&self) -> u32 {
+        self.score_value
+    }
+
+    fn update_position(&mut self, boss_pos: Vector2<f32>) {
+        let target = Vector2::new(boss_pos.x + self.offset.x, boss_pos.y + self.offset.y);
+        let movement = target - self.unit.body.pos;
+        self.unit.body.velocity = Vector2::new(movement.norm(), movement.y.atan2(movement.x));
+    }
+
+    fn update_weapon(&mut self, phase: usize, player_pos: Option<Vector2<f32>>) -> Vec<Projectile> {
+        if self.is_dead() {
+            return vec![];
+        }
+        let origin = self.unit.body.pos;
+        if let Some(weapon) = self.phase_weapons.get_mut(phase) {
+            if weapon.update() {
+                return weapon.fire(origin, player_pos);
+            }
+        }
+        vec![]
+    }
+}
+
+impl Entity for BossPart {
+    fn update(&mut self) {
+        self.unit.update();
+    }
+
+    fn get_unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    fn get_body(&self) -> Body {
+        self.unit.get_body()
+    }
+
+    fn set_body(&mut self, body: Body) {
+        self.unit.set_body(body)
+    }
+
+    fn is_dead(&self) -> bool {
+        self.unit.is_dead
+    }
+
+    fn take_damage(&mut self, damage: u32) -> bool {
+        self.unit.take_damage(damage)
+    }
+
+    fn set_dead(&mut self) {
+        self.unit.set_dead()
+    }
+}
+
+pub struct Boss {
+    parts: Vec<BossPart>,
+    pos: Vector2<f32>,
+    origin_y: f32,
+    target_x: f32,
+    phase: usize,
+    age: u32,
+    defeat_ticks: Option<u32>
+}
+
+impl Boss {
+    pub fn new(x: f32, y: f32, target_x: f32, encounter: u32) -> Boss {
+        let pos = Vector2::new(x, y);
+        let strength = 1.0 + (encounter.saturating_sub(1) as f32 * 0.5);
+        Boss {
+            parts: vec![
+                BossPart::new_turret(pos, TURRET_OFFSET_Y, strength),
+                BossPart::new_turret(pos, -TURRET_OFFSET_Y, strength),
+                BossPart::new_core(pos, strength)
+            ],
+            pos,
+            origin_y: y,
+            target_x,
+            phase: 0,
+            age: 0,
+            defeat_ticks: None
+        }
+    }
+
+    pub fn get_pos(&self) -> Vector2<f32> {
+        self.pos
+    }
+
+    pub fn get_parts(&self) -> &Vec<BossPart> {
+        &self.parts
+    }
+
+    pub fn get_parts_mut(&mut self) -> &mut Vec<BossPart> {
+        &mut self.parts
+    }
+
+    pub fn get_health_fraction(&self) -> f32 {
+        let (hit_points, max_hit_points) = self.parts.iter().fold((0, 0), |(hit_points, max_hit_points), part| {
+            let unit = part.get_unit();
+            (hit_points + if unit.is_dead { 0 } else { unit.hit_points }, max_hit_points + unit.max_hit_points)
+        });
+        if max_hit_points == 0 {
+            0.0
+        } else {
+            hit_points as f32 / max_hit_points as f32
+        }
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.defeat_ticks.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.defeat_ticks == Some(0)
+    }
+
+    pub fn start_defeat(&mut self) {
+        if self.defeat_ticks.is_none() {
+            self.defeat_ticks = Some(DEFEAT_TICKS);
+            for part in &mut self.parts {
+                part.unit.body.collidable = false;
+            }
+        }
+    }
+
+    fn update_phase(&mut self) {
+        let health_fraction = self.get_health_fraction();
+        let phase = BOSS_PHASE_THRESHOLDS.iter().filter(|threshold| health_fraction < **threshold).count();
+        self.phase = self.phase.max(phase);
+    }
+
+    fn update_position(&mut self) {
+        if self.pos.x > self.target_x {
+            self.pos.x = (self.pos.x - BOSS_ENTRY_SPEED).max(self.target_x);
+        } else {
+            self.pos.y = self.origin_y + BOSS_SWAY_AMPLITUDE * (self.age as f32 * BOSS_SWAY_FREQUENCY[self.phase]).sin();
+            self.age += 1;
+        }
+    }
+
+    fn update_defeat(&mut self, particals: &mut Arena<Partical>, rng: &mut XorShiftRng) {
+        if let Some(defeat_ticks) = self.defeat_ticks {
+            if defeat_ticks > 0 {
+                if defeat_ticks % DEFEAT_EXPLOSION_INTERVAL == 0 {
+                    let offset_x: f32 = rng.gen_range(-DEFEAT_EXPLOSION_SPREAD, DEFEAT_EXPLOSION_SPREAD);
+                    let offset_y: f32 = rng.gen_range(-DEFEAT_EXPLOSION_SPREAD, DEFEAT_EXPLOSION_SPREAD);
+                    particals.insert(Partical::new_drone_death(self.pos.x + offset_x, self.pos.y + offset_y));
+                }
+                self.defeat_ticks = Some(defeat_ticks - 1);
+            }
+            if defeat_ticks == 1 {
+                for part in &mut self.parts {
+                    part.set_dead();
+                }
+            }
+        }
+    }
+
+    pub fn update(&mut self, player_pos: Option<Vector2<f32>>, projectiles: &mut Arena<Projectile>, particals: &mut Arena<Partical>, rng: &mut XorShiftRng) {
+        if self.is_defeated() {
+            self.update_defeat(particals, rng);
+        } else {
+            self.update_position();
+            self.update_phase();
+            let entered = self.pos.x <= self.target_x;
+            for part in &mut self.parts {
+                if entered {
+                    projectiles.extend(part.update_weapon(self.phase, player_pos));
+                }
+            }
+        }
+        for part in &mut self.parts {
+            part.update_position(self.pos);
+            part.update();
+        }
+    }
+}
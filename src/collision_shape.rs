@@ -0,0 +1,167 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::collections::HashMap;
+use std::path::Path;
+use nalgebra::{Isometry2, Point2, Vector2};
+use ncollide::shape::{Ball2, Capsule2, ConvexHull2, Compound2, ShapeHandle2};
+use ncollide::query;
+use ncollide::query::Proximity;
+use image;
+use super::body::*;
+
+const SPRITE_HULL_FILES: [&str; 5] = ["playerFighter.png", "drone1.png", "tank1.png", "kamikaze1.png", "splitterFragment1.png"];
+const SPRITE_HULL_ALPHA_THRESHOLD: u8 = 128;
+
+//Shapes are in unscaled sprite pixels around the sprite center with y up, the body's scale and
+//rotation are applied when the shape is placed in the world
+pub enum CollisionShape {
+    Circle(f32), //radius
+    Capsule(f32, f32), //half height along the sprite's y axis, radius
+    ConvexPolygon(Vec<Point2<f32>>),
+    Compound(Vec<(Vector2<f32>, f32, CollisionShape)>), //offset, rotation, shape
+    SpriteHull(&'static str, Box<CollisionShape>) //sprite file, shape used when no hull was loaded for it
+}
+
+impl CollisionShape {
+    pub fn rectangle(half_w: f32, half_h: f32) -> CollisionShape {
+        CollisionShape::ConvexPolygon(vec![
+            Point2::new(-half_w, -half_h),
+            Point2::new(half_w, -half_h),
+            Point2::new(half_w, half_h),
+            Point2::new(-half_w, half_h)
+        ])
+    }
+
+    //Hull around the opaque pixels of a row-major alpha channel, None when nothing is opaque
+    pub fn from_alpha(width: u32, height: u32, alpha: &[u8]) -> Option<CollisionShape> {
+        let mut points = vec![];
+        let to_local = |x: u32, y: u32| Point2::new(x as f32 - width as f32 / 2.0, height as f32 / 2.0 - y as f32);
+        for y in 0..height {
+            let row = &alpha[(y * width) as usize..((y + 1) * width) as usize];
+            let first = row.iter().position(|a| *a >= SPRITE_HULL_ALPHA_THRESHOLD);
+            let last = row.iter().rposition(|a| *a >= SPRITE_HULL_ALPHA_THRESHOLD);
+            if let (Some(first), Some(last)) = (first, last) {
+                let (left, right) = (first as u32, last as u32 + 1);
+                points.extend_from_slice(&[to_local(left, y), to_local(left, y + 1), to_local(right, y), to_local(right, y + 1)]);
+            }
+        }
+        if points.is_empty() {
+            None
+        } else {
+            Some(CollisionShape::ConvexPolygon(convex_hull(points)))
+        }
+    }
+
+    pub fn get_bounding_radius(&self, sprite_hulls: &SpriteHulls, scale: Vector2<f32>) -> f32 {
+        let max_scale = scale.x.max(scale.y);
+        match *self {
+            CollisionShape::Circle(radius) => radius * max_scale,
+            CollisionShape::Capsule(half_height, radius) => half_height * scale.y + radius * scale.x,
+            CollisionShape::ConvexPolygon(ref points) => points.iter()
+                .map(|point| Vector2::new(point.x * scale.x, point.y * scale.y).norm())
+                .fold(0.0, f32::max),
+            CollisionShape::Compound(ref parts) => parts.iter()
+                .map(|&(offset, _, ref shape)| Vector2::new(offset.x * scale.x, offset.y * scale.y).norm() + shape.get_bounding_radius(sprite_hulls, scale))
+                .fold(0.0, f32::max),
+            CollisionShape::SpriteHull(..) => sprite_hulls.resolve(self).get_bounding_radius(sprite_hulls, scale)
+        }
+    }
+
+    pub fn create_shape_handle(&self, sprite_hulls: &SpriteHulls, scale: Vector2<f32>) -> ShapeHandle2<f32> {
+        match *self {
+            CollisionShape::Circle(radius) => ShapeHandle2::new(Ball2::new(radius * scale.x.max(scale.y))),
+            CollisionShape::Capsule(half_height, radius) => ShapeHandle2::new(Capsule2::new(half_height * scale.y, radius * scale.x)),
+            CollisionShape::ConvexPolygon(ref points) => ShapeHandle2::new(ConvexHull2::new(
+                points.iter().map(|point| Point2::new(point.x * scale.x, point.y * scale.y)).collect()
+            )),
+            CollisionShape::Compound(ref parts) => ShapeHandle2::new(Compound2::new(
+                parts.iter()
+                    .map(|&(offset, rotation, ref shape)| (
+                        Isometry2::new(Vector2::new(offset.x * scale.x, offset.y * scale.y), rotation),
+                        shape.create_shape_handle(sprite_hulls, scale)
+                    ))
+                    .collect()
+            )),
+            CollisionShape::SpriteHull(..) => sprite_hulls.resolve(self).create_shape_handle(sprite_hulls, scale)
+        }
+    }
+}
+
+//Andrew's monotone chain, counter-clockwise without repeated or collinear points
+fn convex_hull(mut points: Vec<Point2<f32>>) -> Vec<Point2<f32>> {
+    points.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: &Point2<f32>, a: &Point2<f32>, b: &Point2<f32>| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point2<f32>> = vec![];
+    for pass in 0..2 {
+        let start = hull.len();
+        let ordered: Vec<&Point2<f32>> = if pass == 0 { points.iter().collect() } else { points.iter().rev().collect() };
+        for point in ordered {
+            while hull.len() >= start + 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+//Sprites render clockwise in screen space with y down, which is counter-clockwise negated in the world
+pub fn get_body_isometry(body: &Body) -> Isometry2<f32> {
+    Isometry2::new(body.pos, -body.rotation)
+}
+
+pub fn is_body_collision(sprite_hulls: &SpriteHulls, a: &Body, b: &Body) -> bool {
+    let a_shape = a.shape.create_shape_handle(sprite_hulls, a.scale);
+    let b_shape = b.shape.create_shape_handle(sprite_hulls, b.scale);
+    query::proximity(&get_body_isometry(a), a_shape.as_ref(), &get_body_isometry(b), b_shape.as_ref(), 0.0) == Proximity::Intersecting
+}
+
+//Fraction of this tick's movement at which the bodies first touch, sweeping both back from where
+//update_pos left them so fast bodies can't pass through thin ones between ticks
+pub fn get_body_time_of_impact(sprite_hulls: &SpriteHulls, a: &Body, b: &Body) -> Option<f32> {
+    let a_shape = a.shape.create_shape_handle(sprite_hulls, a.scale);
+    let b_shape = b.shape.create_shape_handle(sprite_hulls, b.scale);
+    let a_movement = a.get_movement_vector();
+    let b_movement = b.get_movement_vector();
+    let a_start = Isometry2::new(a.pos - a_movement, -a.rotation);
+    let b_start = Isometry2::new(b.pos - b_movement, -b.rotation);
+    query::time_of_impact(&a_start, &a_movement, a_shape.as_ref(), &b_start, &b_movement, b_shape.as_ref())
+        .filter(|time_of_impact| *time_of_impact <= 1.0)
+}
+
+#[derive(Default)]
+pub struct SpriteHulls {
+    hulls: HashMap<&'static str, CollisionShape>
+}
+
+impl SpriteHulls {
+    //Builds the hull of every sprite an entity can ask for, once, so the shapes are fixed for the whole run
+    pub fn load(assets_path: &Path) -> image::ImageResult<SpriteHulls> {
+        let mut hulls = HashMap::new();
+        for file in SPRITE_HULL_FILES.iter() {
+            let sprite = image::open(assets_path.join(file))?.to_rgba();
+            let alpha: Vec<u8> = sprite.pixels().map(|pixel| pixel.data[3]).collect();
+            if let Some(hull) = CollisionShape::from_alpha(sprite.width(), sprite.height(), &alpha) {
+                hulls.insert(*file, hull);
+            }
+        }
+        Ok(SpriteHulls {
+            hulls
+        })
+    }
+
+    pub fn resolve<'a>(&'a self, shape: &'a CollisionShape) -> &'a CollisionShape {
+        match *shape {
+            CollisionShape::SpriteHull(file, ref fallback) => self.hulls.get(file).unwrap_or(fallback),
+            _ => shape
+        }
+    }
+}
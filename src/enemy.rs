@@ -4,41 +4,300 @@ Please see the file LICENSE in this distribution
 for license terms. */
 
 use std::f32;
-use ggez::*;
-use nalgebra::Vector2;
+use nalgebra::{Point2, Vector2};
 use super::entity::*;
 use super::body::*;
-use super::asset_manager::*;
-use super::camera::*;
+use super::collision_shape::*;
 use super::unit::*;
+use super::projectile::*;
+use super::enemy_weapon::*;
+use super::wave_definition::WaveModifiers;
 
 const ENEMY_DIRECTION: f32 = f32::consts::PI;
 const MOVE_SPEED_NORMAL: f32 = 5.0;
+const FIRE_DELAY_NORMAL: u64 = 2500;
+const PROJECTILE_SPEED_NORMAL: f32 = 8.0;
+const FIRE_DELAY_GUNNER: u64 = 1800;
+const FIRE_DELAY_SPREAD: u64 = 3000;
+const SPREAD_ARC: f32 = f32::consts::PI / 3.0;
+const WEAVER_AMPLITUDE: f32 = 90.0;
+const WEAVER_FREQUENCY: f32 = 0.06;
+const KAMIKAZE_TURN_RATE: f32 = 0.05;
+const SPLITTER_FRAGMENTS: u32 = 3;
+const SPLITTER_FRAGMENT_SPREAD: f32 = f32::consts::PI / 4.0;
 
+#[derive(Clone, Copy, PartialEq, Deserialize)]
 pub enum EnemyType {
-    NormalDrone
+    NormalDrone,
+    GunnerDrone,
+    SpreadDrone,
+    Weaver,
+    Kamikaze,
+    Tank,
+    Splitter,
+    SplitterFragment
+}
+
+#[derive(Clone, Copy)]
+enum Movement {
+    Straight,
+    SineWave(f32, f32, f32), //origin y, amplitude, frequency
+    Homing(f32) //turn rate
+}
+
+struct EnemyStats {
+    asset_key: &'static str,
+    shape: CollisionShape,
+    scale: f32,
+    move_speed: f32,
+    movement: Movement,
+    hit_points: u32,
+    score_value: u32
 }
 
 pub struct Enemy {
-    unit: Unit
+    unit: Unit,
+    enemy_type: EnemyType,
+    weapon: Option<EnemyWeapon>,
+    movement: Movement,
+    move_speed: f32,
+    score_value: u32,
+    age: u32
 }
 
 impl Enemy {
-    fn new(x: f32, y: f32, asset_key: String, move_speed: f32) -> Enemy {
-        let mut body = Body::new(x, y, 132.0, 128.0, 0.5, 0.5, (f32::consts::PI*3.0)/2.0, true);
-        body.velocity = Vector2::new(move_speed, ENEMY_DIRECTION);
+    fn new(x: f32, y: f32, enemy_type: EnemyType, stats: EnemyStats, weapon: Option<EnemyWeapon>) -> Enemy {
+        let mut body = Body::new(x, y, 132.0, 128.0, stats.scale, stats.scale, (f32::consts::PI*3.0)/2.0, true);
+        body.set_shape(stats.shape);
+        body.velocity = Vector2::new(stats.move_speed, ENEMY_DIRECTION);
+        let mut unit = Unit::new(body, stats.asset_key.to_string(), 1, 1, true);
+        unit.set_max_hit_points(stats.hit_points);
         Enemy {
-            unit: Unit::new(body, asset_key, 1, 1, true)
+            unit,
+            enemy_type,
+            weapon,
+            movement: stats.movement,
+            move_speed: stats.move_speed,
+            score_value: stats.score_value,
+            age: 0
         }
     }
 
+    fn create_drone_shape() -> CollisionShape {
+        CollisionShape::SpriteHull("drone1.png", Box::new(CollisionShape::Capsule(20.0, 48.0)))
+    }
+
     pub fn new_drone(x:f32, y: f32) -> Enemy {
-        Enemy::new(x, y, "drone1".to_string(), MOVE_SPEED_NORMAL)
+        Enemy::new(x, y, EnemyType::NormalDrone, EnemyStats {
+            asset_key: "drone1",
+            shape: Enemy::create_drone_shape(),
+            scale: 0.5,
+            move_speed: MOVE_SPEED_NORMAL,
+            movement: Movement::Straight,
+            hit_points: 1,
+            score_value: 150
+        }, Some(EnemyWeapon::new(
+            FIRE_DELAY_NORMAL,
+            AimMode::Straight,
+            ProjectilePattern { count: 1, speed: PROJECTILE_SPEED_NORMAL, damage: 1 }
+        )))
+    }
+
+    pub fn new_gunner_drone(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::GunnerDrone, EnemyStats {
+            asset_key: "drone_gunner",
+            shape: Enemy::create_drone_shape(),
+            scale: 0.5,
+            move_speed: MOVE_SPEED_NORMAL,
+            movement: Movement::Straight,
+            hit_points: 1,
+            score_value: 200
+        }, Some(EnemyWeapon::new(
+            FIRE_DELAY_GUNNER,
+            AimMode::AtPlayer,
+            ProjectilePattern { count: 2, speed: PROJECTILE_SPEED_NORMAL, damage: 1 }
+        )))
+    }
+
+    pub fn new_spread_drone(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::SpreadDrone, EnemyStats {
+            asset_key: "drone_spread",
+            shape: Enemy::create_drone_shape(),
+            scale: 0.5,
+            move_speed: MOVE_SPEED_NORMAL,
+            movement: Movement::Straight,
+            hit_points: 1,
+            score_value: 250
+        }, Some(EnemyWeapon::new(
+            FIRE_DELAY_SPREAD,
+            AimMode::Spread(SPREAD_ARC),
+            ProjectilePattern { count: 5, speed: PROJECTILE_SPEED_NORMAL * 0.75, damage: 1 }
+        )))
+    }
+
+    pub fn new_weaver(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::Weaver, EnemyStats {
+            asset_key: "drone_weaver",
+            shape: Enemy::create_drone_shape(),
+            scale: 0.45,
+            move_speed: MOVE_SPEED_NORMAL * 0.8,
+            movement: Movement::SineWave(y, WEAVER_AMPLITUDE, WEAVER_FREQUENCY),
+            hit_points: 1,
+            score_value: 200
+        }, None)
+    }
+
+    pub fn new_kamikaze(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::Kamikaze, EnemyStats {
+            asset_key: "kamikaze",
+            shape: CollisionShape::SpriteHull("kamikaze1.png", Box::new(CollisionShape::ConvexPolygon(vec![
+                Point2::new(0.0, 48.0),
+                Point2::new(-28.0, -32.0),
+                Point2::new(0.0, -44.0),
+                Point2::new(28.0, -32.0)
+            ]))),
+            scale: 0.4,
+            move_speed: MOVE_SPEED_NORMAL * 1.8,
+            movement: Movement::Homing(KAMIKAZE_TURN_RATE),
+            hit_points: 1,
+            score_value: 300
+        }, None)
+    }
+
+    pub fn new_tank(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::Tank, EnemyStats {
+            asset_key: "tank",
+            shape: CollisionShape::SpriteHull("tank1.png", Box::new(CollisionShape::rectangle(60.0, 54.0))),
+            scale: 0.8,
+            move_speed: MOVE_SPEED_NORMAL * 0.5,
+            movement: Movement::Straight,
+            hit_points: 5,
+            score_value: 500
+        }, Some(EnemyWeapon::new(
+            FIRE_DELAY_NORMAL,
+            AimMode::AtPlayer,
+            ProjectilePattern { count: 1, speed: PROJECTILE_SPEED_NORMAL, damage: 2 }
+        )))
+    }
+
+    pub fn new_splitter(x:f32, y: f32) -> Enemy {
+        Enemy::new(x, y, EnemyType::Splitter, EnemyStats {
+            asset_key: "drone_splitter",
+            shape: Enemy::create_drone_shape(),
+            scale: 0.6,
+            move_speed: MOVE_SPEED_NORMAL * 0.7,
+            movement: Movement::Straight,
+            hit_points: 2,
+            score_value: 250
+        }, None)
+    }
+
+    pub fn new_splitter_fragment(x:f32, y: f32, direction: f32) -> Enemy {
+        let mut fragment = Enemy::new(x, y, EnemyType::SplitterFragment, EnemyStats {
+            asset_key: "splitter_fragment",
+            shape: CollisionShape::SpriteHull("splitterFragment1.png", Box::new(CollisionShape::Circle(20.0))),
+            scale: 0.3,
+            move_speed: MOVE_SPEED_NORMAL * 1.2,
+            movement: Movement::Straight,
+            hit_points: 1,
+            score_value: 100
+        }, None);
+        fragment.set_heading(direction);
+        fragment
     }
 
     pub fn create_enemy_by_key(enemy_type: EnemyType, x:f32, y: f32) -> Option<Enemy> {
         match enemy_type {
-            EnemyType::NormalDrone => Some(Enemy::new_drone(x, y))
+            EnemyType::NormalDrone => Some(Enemy::new_drone(x, y)),
+            EnemyType::GunnerDrone => Some(Enemy::new_gunner_drone(x, y)),
+            EnemyType::SpreadDrone => Some(Enemy::new_spread_drone(x, y)),
+            EnemyType::Weaver => Some(Enemy::new_weaver(x, y)),
+            EnemyType::Kamikaze => Some(Enemy::new_kamikaze(x, y)),
+            EnemyType::Tank => Some(Enemy::new_tank(x, y)),
+            EnemyType::Splitter => Some(Enemy::new_splitter(x, y)),
+            EnemyType::SplitterFragment => Some(Enemy::new_splitter_fragment(x, y, ENEMY_DIRECTION))
+        }
+    }
+
+    pub fn apply_modifiers(&mut self, modifiers: &WaveModifiers) {
+        self.move_speed *= modifiers.speed;
+        self.unit.body.velocity[0] = self.move_speed;
+        let hit_points = ((self.unit.max_hit_points as f32) * modifiers.health).ceil() as u32;
+        self.unit.set_max_hit_points(hit_points);
+        if let Some(ref mut weapon) = self.weapon {
+            weapon.scale_fire_rate(modifiers.fire_rate);
+        }
+    }
+
+    pub fn get_score_value(&self) -> u32 {
+        self.score_value
+    }
+
+    pub fn get_weapon_mut(&mut self) -> Option<&mut EnemyWeapon> {
+        self.weapon.as_mut()
+    }
+
+    pub fn update_weapon(&mut self, player_pos: Option<Vector2<f32>>) -> Vec<Projectile> {
+        if self.is_dead() {
+            return vec![];
+        }
+        let origin = self.unit.body.pos;
+        if let Some(ref mut weapon) = self.weapon {
+            if weapon.update() {
+                return weapon.fire(origin, player_pos);
+            }
+        }
+        vec![]
+    }
+
+    fn set_heading(&mut self, direction: f32) {
+        self.unit.body.velocity = Vector2::new(self.move_speed, direction);
+        self.unit.body.rotation = (f32::consts::PI/2.0) - direction;
+    }
+
+    fn set_movement_vector(&mut self, movement_vector: Vector2<f32>) {
+        let direction = movement_vector.y.atan2(movement_vector.x);
+        self.unit.body.velocity = Vector2::new(movement_vector.norm(), direction);
+        self.unit.body.rotation = (f32::consts::PI/2.0) - direction;
+    }
+
+    pub fn update_movement(&mut self, player_pos: Option<Vector2<f32>>) {
+        match self.movement {
+            Movement::Straight => {},
+            Movement::SineWave(origin_y, amplitude, frequency) => {
+                let next_y = origin_y + amplitude * ((self.age + 1) as f32 * frequency).sin();
+                let movement_vector = Vector2::new(-self.move_speed, next_y - self.unit.body.pos.y);
+                self.set_movement_vector(movement_vector);
+            },
+            Movement::Homing(turn_rate) => {
+                if let Some(target) = player_pos {
+                    let pos = self.unit.body.pos;
+                    let heading = self.unit.body.velocity[1];
+                    let target_heading = (target.y - pos.y).atan2(target.x - pos.x);
+                    let mut turn = target_heading - heading;
+                    while turn > f32::consts::PI {
+                        turn -= f32::consts::PI * 2.0;
+                    }
+                    while turn < -f32::consts::PI {
+                        turn += f32::consts::PI * 2.0;
+                    }
+                    self.set_heading(heading + turn.max(-turn_rate).min(turn_rate));
+                }
+            }
+        }
+        self.age += 1;
+    }
+
+    pub fn get_death_spawns(&self) -> Vec<Enemy> {
+        match self.enemy_type {
+            EnemyType::Splitter => {
+                let pos = self.unit.body.pos;
+                (0..SPLITTER_FRAGMENTS).map(|i| {
+                    let offset = i as f32 - (SPLITTER_FRAGMENTS - 1) as f32 / 2.0;
+                    Enemy::new_splitter_fragment(pos.x, pos.y, ENEMY_DIRECTION + offset * SPLITTER_FRAGMENT_SPREAD)
+                }).collect()
+            },
+            _ => vec![]
         }
     }
 }
@@ -48,8 +307,8 @@ impl Entity for Enemy {
         self.unit.update();
     }
 
-    fn draw(&self, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        self.unit.draw(asset_manager, ctx, interpolation_value, camera);
+    fn get_unit(&self) -> &Unit {
+        &self.unit
     }
 
     fn get_body(&self) -> Body {
@@ -64,6 +323,10 @@ impl Entity for Enemy {
         self.unit.is_dead
     }
 
+    fn take_damage(&mut self, damage: u32) -> bool {
+        self.unit.take_damage(damage)
+    }
+
     fn set_dead(&mut self) {
         self.unit.set_dead()
     }
@@ -0,0 +1,111 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::f32;
+use nalgebra::Vector2;
+use super::projectile::*;
+use super::simulation::ms_to_ticks;
+
+const STRAIGHT_ANGLE: f32 = f32::consts::PI;
+const VOLLEY_SPACING: f32 = 12.0;
+
+#[derive(Clone, Copy)]
+pub enum AimMode {
+    Straight,
+    AtPlayer,
+    Spread(f32) //arc
+}
+
+#[derive(Clone, Copy)]
+pub struct ProjectilePattern {
+    pub count: u32,
+    pub speed: f32,
+    pub damage: u32
+}
+
+#[derive(Clone)]
+pub struct EnemyWeapon {
+    fire_delay: u64,
+    cooldown: u64,
+    aim_mode: AimMode,
+    pattern: ProjectilePattern
+}
+
+impl EnemyWeapon {
+    pub fn new(fire_delay_ms: u64, aim_mode: AimMode, pattern: ProjectilePattern) -> EnemyWeapon {
+        let fire_delay = ms_to_ticks(fire_delay_ms);
+        EnemyWeapon {
+            fire_delay,
+            cooldown: fire_delay,
+            aim_mode,
+            pattern
+        }
+    }
+
+    pub fn add_cooldown(&mut self, ticks: u64) {
+        self.cooldown += ticks;
+    }
+
+    pub fn scale_fire_rate(&mut self, fire_rate: f32) {
+        self.fire_delay = ((self.fire_delay as f32 / fire_rate) as u64).max(1);
+        self.cooldown = self.fire_delay;
+    }
+
+    pub fn get_fire_delay(&self) -> u64 {
+        self.fire_delay
+    }
+
+    pub fn update(&mut self) -> bool {
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            false
+        } else {
+            self.cooldown = self.fire_delay;
+            true
+        }
+    }
+
+    fn get_aim_angle(origin: &Vector2<f32>, target: Option<Vector2<f32>>) -> f32 {
+        match target {
+            Some(target) => (target.y - origin.y).atan2(target.x - origin.x),
+            None => STRAIGHT_ANGLE
+        }
+    }
+
+    fn create_volley(&self, origin: &Vector2<f32>, angle: f32) -> Vec<Projectile> {
+        let count = self.pattern.count.max(1);
+        let first_offset = (count - 1) as f32 * VOLLEY_SPACING / 2.0;
+        (0..count).map(|i| {
+            let offset = i as f32 * VOLLEY_SPACING - first_offset;
+            Projectile::new_enemy(
+                origin.x - angle.sin() * offset,
+                origin.y + angle.cos() * offset,
+                angle,
+                self.pattern.speed,
+                self.pattern.damage
+            )
+        }).collect()
+    }
+
+    fn create_spread(&self, origin: &Vector2<f32>, arc: f32) -> Vec<Projectile> {
+        let count = self.pattern.count.max(1);
+        if count == 1 {
+            return vec![Projectile::new_enemy(origin.x, origin.y, STRAIGHT_ANGLE, self.pattern.speed, self.pattern.damage)];
+        }
+        let angle_step = arc / (count - 1) as f32;
+        (0..count).map(|i| {
+            let angle = STRAIGHT_ANGLE - (arc / 2.0) + angle_step * i as f32;
+            Projectile::new_enemy(origin.x, origin.y, angle, self.pattern.speed, self.pattern.damage)
+        }).collect()
+    }
+
+    pub fn fire(&self, origin: Vector2<f32>, player_pos: Option<Vector2<f32>>) -> Vec<Projectile> {
+        match self.aim_mode {
+            AimMode::Straight => self.create_volley(&origin, STRAIGHT_ANGLE),
+            AimMode::AtPlayer => self.create_volley(&origin, EnemyWeapon::get_aim_angle(&origin, player_pos)),
+            AimMode::Spread(arc) => self.create_spread(&origin, arc)
+        }
+    }
+}
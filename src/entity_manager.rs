@@ -3,132 +3,554 @@ This work is available under the "MIT License”.
 Please see the file LICENSE in this distribution
 for license terms. */
 
-use ggez::*;
-use std::time::{Instant};
+use std::f32;
 use nalgebra::{Isometry2, Vector2, Point, Point2, Id};
-use ncollide::shape::Cuboid2;
+use rand::XorShiftRng;
+use ncollide::shape::Ball2;
 use ncollide::bounding_volume;
 use ncollide::bounding_volume::BoundingVolume;
 use ncollide::query::PointQuery;
 
-use super::asset_manager::*;
 use super::entity::*;
+use super::body::*;
 use super::enemy::*;
-use super::camera::*;
+use super::boss::*;
 use super::projectile::*;
 use super::particals::*;
+use super::pickup::*;
+use super::score::*;
 use super::play_space::*;
-use super::game_event_handler::GameState;
+use super::arena::*;
+use super::collision_shape::*;
+use super::game_state::{GameState, PlayerState};
+use super::player_weapon::{BEAM_HALF_WIDTH, BEAM_DAMAGE};
+use super::input::Direction;
+
+const RAM_DAMAGE: u32 = 2;
+const BOMB_DAMAGE: u32 = 10;
+
+enum ProjectileTarget {
+    Enemy(EntityId),
+    BossPart(usize),
+    Player(usize)
+}
 
 pub struct EntityManager;
 
 impl EntityManager {
-    pub fn is_player_alive(game_state: &GameState) -> bool {
-        !game_state.player.is_dead()
+    pub fn is_player_alive(game_state: &GameState, player: usize) -> bool {
+        !game_state.players[player].player.is_dead()
     }
 
-    pub fn respawn_player(game_state: &mut GameState) {
-        let mut body = game_state.player.get_body();
-        body.pos = Vector2::new(game_state.play_space.player_area.w / 3.0, game_state.play_space.player_area.h / 2.0);
-        game_state.player.set_body(body);
-        game_state.player.set_alive();
+    pub fn can_player_respawn(game_state: &GameState, player: usize) -> bool {
+        !EntityManager::is_player_alive(game_state, player) && !game_state.players[player].is_out()
     }
 
-    pub fn get_player_last_death(game_state: &GameState) -> Instant {
-        game_state.player.last_death
+    pub fn respawn_player(game_state: &mut GameState, player: usize) {
+        let player_count = game_state.players.len();
+        let player_lane_y = GameState::get_player_lane_y(&game_state.play_space, player, player_count);
+        let respawned = &mut game_state.players[player].player;
+        let mut body = respawned.get_body();
+        body.pos = Vector2::new(game_state.play_space.player_area.w / 3.0, player_lane_y);
+        respawned.set_body(body);
+        respawned.set_alive();
+    }
+
+    pub fn get_player_last_death(game_state: &GameState, player: usize) -> u64 {
+        game_state.players[player].player.last_death
     }
 
     pub fn add_enemy(game_state: &mut GameState, enemy: Enemy) {
-        game_state.enemies.push(enemy);
+        game_state.enemies.insert(enemy);
     }
 
     pub fn get_enemy_count(game_state: &GameState) -> u32 {
         game_state.enemies.len() as u32
     }
 
+    pub fn add_boss(game_state: &mut GameState, boss: Boss) {
+        game_state.boss = Some(boss);
+    }
+
+    pub fn is_boss_active(game_state: &GameState) -> bool {
+        game_state.boss.is_some()
+    }
+
+    //Ties go to the first position so targeting stays deterministic
+    fn get_nearest<K, I: Iterator<Item = (K, Vector2<f32>)>>(targets: I, pos: Vector2<f32>) -> Option<(K, Vector2<f32>)> {
+        targets.fold(None, |nearest: Option<(K, Vector2<f32>)>, target| match nearest {
+            Some(nearest) if (nearest.1 - pos).norm() <= (target.1 - pos).norm() => Some(nearest),
+            _ => Some(target)
+        })
+    }
+
+    fn get_player_target(players: &Vec<PlayerState>, pos: Vector2<f32>) -> Option<Vector2<f32>> {
+        let player_positions = players.iter()
+            .filter(|state| !state.player.is_dead())
+            .map(|state| ((), state.player.get_body().pos));
+        EntityManager::get_nearest(player_positions, pos).map(|nearest| nearest.1)
+    }
+
     pub fn update(game_state: &mut GameState) {
-        game_state.player.update();
+        for state in &mut game_state.players {
+            state.score.update(game_state.tick);
+            state.player.update();
+        }
+        EntityManager::update_player_bounds(game_state);
         for enemy in &mut game_state.enemies {
+            let player_target = EntityManager::get_player_target(&game_state.players, enemy.get_body().pos);
+            enemy.update_movement(player_target);
             enemy.update();
         }
-        for projectile in game_state.projectiles.iter_mut(){
+        EntityManager::update_player_fire(game_state);
+        EntityManager::update_enemy_fire(game_state);
+        EntityManager::update_boss(game_state);
+        EntityManager::update_homing(game_state);
+        for projectile in game_state.projectiles.values_mut(){
             projectile.update();
         } 
-        for partical in game_state.particals.iter_mut(){
+        for partical in game_state.particals.values_mut(){
             partical.update();
         } 
+        for pickup in game_state.pickups.iter_mut(){
+            pickup.update();
+        }
         EntityManager::collision_resolution(game_state);
         EntityManager::update_clean_up(game_state);
     }
 
-    pub fn update_life_lost(game_state: &mut GameState) -> u32 {
-        let mut lost = 0;
+    fn update_player_bounds(game_state: &mut GameState) {
+        for state in &mut game_state.players {
+            if state.player.is_dead() {
+                continue;
+            }
+            let mut body = state.player.get_body();
+            let confined_pos = game_state.play_space.confine_player(body.pos);
+            if confined_pos != body.pos {
+                //The velocity is kept to the move actually made so drawing and sweeps don't run past the edge
+                let movement = confined_pos - (body.pos - body.get_movement_vector());
+                body.velocity = Vector2::new(movement.norm(), movement.y.atan2(movement.x));
+                body.pos = confined_pos;
+                state.player.set_body(body);
+            }
+        }
+    }
+
+    fn update_player_fire(game_state: &mut GameState) {
+        for (i, state) in game_state.players.iter_mut().enumerate() {
+            let mut projectiles = state.player.update_weapon();
+            for projectile in &mut projectiles {
+                projectile.set_owner(i);
+            }
+            game_state.projectiles.extend(projectiles);
+        }
+    }
+
+    //Enemies come with their id so they can be locked on to, boss parts are only chased by position
+    fn get_nearest_target(enemies: &Arena<Enemy>, boss: &Option<Boss>, pos: Vector2<f32>) -> Option<(Option<EntityId>, Vector2<f32>)> {
+        let enemy_targets = enemies.iter()
+            .filter(|&&(_, ref enemy)| !enemy.is_dead())
+            .map(|&(id, ref enemy)| (Some(id), enemy.get_body().pos));
+        let boss_targets = boss.iter()
+            .flat_map(|boss| boss.get_parts().iter())
+            .filter(|part| !part.is_dead() && part.get_body().collidable)
+            .map(|part| (None, part.get_body().pos));
+        let targets_ahead = enemy_targets.chain(boss_targets)
+            .filter(|target| target.1.x > pos.x);
+        EntityManager::get_nearest(targets_ahead, pos)
+    }
+
+    fn update_homing(game_state: &mut GameState) {
+        let enemies = &game_state.enemies;
+        for projectile in game_state.projectiles.values_mut() {
+            if projectile.is_homing() {
+                //Missiles stay on the enemy they locked on to and only pick again once it is gone
+                let locked_target = projectile.get_target()
+                    .and_then(|id| enemies.get(id))
+                    .filter(|enemy| !enemy.is_dead())
+                    .map(|enemy| enemy.get_body().pos);
+                let target = match locked_target {
+                    Some(target) => Some(target),
+                    None => {
+                        let nearest = EntityManager::get_nearest_target(enemies, &game_state.boss, projectile.get_body().pos);
+                        projectile.set_target(nearest.and_then(|nearest| nearest.0));
+                        nearest.map(|nearest| nearest.1)
+                    }
+                };
+                projectile.update_homing(target);
+            }
+        }
+    }
+
+    fn update_enemy_fire(game_state: &mut GameState) {
         for enemy in &mut game_state.enemies {
-            if let Some(enemy_area) = EntityManager::create_entity_collision_area(enemy) {
+            let player_target = EntityManager::get_player_target(&game_state.players, enemy.get_body().pos);
+            let projectiles = enemy.update_weapon(player_target);
+            game_state.projectiles.extend(projectiles);
+        }
+    }
+
+    fn update_boss(game_state: &mut GameState) {
+        if let Some(ref mut boss) = game_state.boss {
+            let player_target = EntityManager::get_player_target(&game_state.players, boss.get_pos());
+            boss.update(player_target, &mut game_state.projectiles, &mut game_state.particals, &mut game_state.rng);
+        }
+        let boss_finished = game_state.boss.as_ref().map_or(false, |boss| boss.is_finished());
+        if boss_finished {
+            game_state.boss = None;
+        }
+    }
+
+    //Where each enemy that got past the players this tick escaped from
+    pub fn update_life_lost(game_state: &mut GameState) -> Vec<Vector2<f32>> {
+        let mut escapes = vec![];
+        for enemy in &mut game_state.enemies {
+            if let Some(enemy_area) = EntityManager::create_entity_collision_area(&game_state.sprite_hulls, enemy) {
                 if game_state.play_space.life_loss_area_aabb.contains(&enemy_area) {
-                    lost += 1;
+                    escapes.push(enemy.get_body().pos);
                     enemy.set_dead();
                 }
             }
         }
-        lost
+        escapes
     }
 
-    fn create_entity_collision_area(entity: &Entity) -> Option<bounding_volume::AABB<Point<f32, nalgebra::U2>>> {
-        let body = entity.get_body();
-        if body.collidable {
-            Some(bounding_volume::aabb(
-                &Cuboid2::new(body.get_scaled_size()),
-                &Isometry2::new(
-                    Vector2::new(body.pos.x, body.pos.y), 
-                    0.0)
-                )
+    //The nearest ship still flying lets the enemy through, or the nearest player still in the run while every ship is respawning
+    fn get_escape_player(players: &Vec<PlayerState>, pos: Vector2<f32>) -> Option<usize> {
+        let in_run = || players.iter().enumerate().filter(|&(_, state)| !state.is_out());
+        let flying = in_run()
+            .filter(|&(_, state)| !state.player.is_dead())
+            .map(|(i, state)| (i, state.player.get_body().pos));
+        let respawning = in_run().map(|(i, state)| (i, state.player.get_body().pos));
+        EntityManager::get_nearest(flying, pos)
+            .or_else(|| EntityManager::get_nearest(respawning, pos))
+            .map(|nearest| nearest.0)
+    }
+
+    //Each escaped enemy costs one life from one player
+    pub fn lose_lives(game_state: &mut GameState, escapes: &[Vector2<f32>]) {
+        for &pos in escapes {
+            let player = match EntityManager::get_escape_player(&game_state.players, pos) {
+                Some(player) => player,
+                None => return
+            };
+            let state = &mut game_state.players[player];
+            state.lives -= 1;
+            //Out of lives takes the ship out of the run for good
+            if state.is_out() && !state.player.is_dead() {
+                EntityManager::ship_death(&mut state.player, &mut game_state.particals);
+            }
+        }
+    }
+
+    //Bounds the shape at any rotation, the exact shapes are only compared once these overlap
+    fn create_body_area(sprite_hulls: &SpriteHulls, body: &Body, pos: Vector2<f32>) -> bounding_volume::AABB<Point<f32, nalgebra::U2>> {
+        bounding_volume::aabb(
+            &Ball2::new(body.shape.get_bounding_radius(sprite_hulls, body.scale)),
+            &Isometry2::new(
+                Vector2::new(pos.x, pos.y), 
+                0.0)
             )
+    }
+
+    pub fn create_entity_collision_area(sprite_hulls: &SpriteHulls, entity: &Entity) -> Option<bounding_volume::AABB<Point<f32, nalgebra::U2>>> {
+        let body = &entity.get_unit().body;
+        if body.collidable {
+            Some(EntityManager::create_body_area(sprite_hulls, body, body.pos))
         } else {
             None
         }
     }
 
-    fn ship_hit_by_projectile(ship: &mut Entity, projectile: &mut Projectile, particals: &mut Vec<Partical>) {
+    //Covers the entity over the whole tick, from where it started to where update_pos left it
+    fn create_entity_swept_area(sprite_hulls: &SpriteHulls, entity: &Entity) -> Option<bounding_volume::AABB<Point<f32, nalgebra::U2>>> {
+        let body = &entity.get_unit().body;
+        EntityManager::create_entity_collision_area(sprite_hulls, entity).map(|area| {
+            let start_area = EntityManager::create_body_area(sprite_hulls, body, body.pos - body.get_movement_vector());
+            area.merged(&start_area)
+        })
+    }
+
+    fn is_swept_area_entity_collision(sprite_hulls: &SpriteHulls, swept_area: &bounding_volume::AABB<Point<f32, nalgebra::U2>>, entity: &Entity) -> bool {
+        EntityManager::create_entity_swept_area(sprite_hulls, entity).map_or(false, |entity_area| swept_area.intersects(&entity_area))
+    }
+
+    fn get_time_of_impact(sprite_hulls: &SpriteHulls, projectile: &Projectile, target: &Entity) -> Option<f32> {
+        get_body_time_of_impact(sprite_hulls, &projectile.get_unit().body, &target.get_unit().body)
+    }
+
+    fn ship_hit_by_projectile(ship: &mut Entity, projectile: &mut Projectile, particals: &mut Arena<Partical>) -> bool {
         projectile.set_dead(); 
-        EntityManager::ship_death(ship, particals);
+        let killed = ship.take_damage(projectile.get_damage());
+        if killed {
+            EntityManager::ship_death(ship, particals);
+        }
+        killed
     }
 
-    fn ship_death(ship: &mut Entity, particals: &mut Vec<Partical>) {
+    fn ship_death(ship: &mut Entity, particals: &mut Arena<Partical>) {
         ship.set_dead(); 
         let ship_body = ship.get_body();
-        particals.push(Partical::new_drone_death(ship_body.pos.x + (ship_body.size.x / 1.5), ship_body.pos.y));
+        particals.insert(Partical::new_drone_death(ship_body.pos.x + (ship_body.size.x / 1.5), ship_body.pos.y));
+    }
+
+    fn enemy_death(enemy: &mut Enemy, particals: &mut Arena<Partical>, spawned_enemies: &mut Vec<Enemy>, pickups: &mut Vec<Pickup>, rng: &mut XorShiftRng) {
+        EntityManager::ship_death(enemy, particals);
+        spawned_enemies.extend(enemy.get_death_spawns());
+        let enemy_pos = enemy.get_body().pos;
+        if let Some(pickup) = Pickup::create_random_drop(enemy_pos.x, enemy_pos.y, rng) {
+            pickups.push(pickup);
+        }
+    }
+
+    fn enemy_hit(enemy: &mut Enemy, damage: u32, score: &mut Score, tick: u64, particals: &mut Arena<Partical>, spawned_enemies: &mut Vec<Enemy>, pickups: &mut Vec<Pickup>, rng: &mut XorShiftRng) {
+        if enemy.take_damage(damage) {
+            EntityManager::enemy_death(enemy, particals, spawned_enemies, pickups, rng);
+            score.add_kill(enemy.get_score_value(), enemy.get_body().pos, tick);
+        }
     }
 
-    fn collision_resolution(game_state: &mut GameState) {
-        if let Some(player_col_area) = EntityManager::create_entity_collision_area(&game_state.player) {
-            if EntityManager::is_player_alive(game_state) {
-                for enemy in &mut game_state.enemies {
-                    if EntityManager::is_col_area_entity_collision(&player_col_area, enemy) {
-                        //Player hit by ship
-                        EntityManager::ship_death(&mut game_state.player, &mut game_state.particals);
-                        EntityManager::ship_death(enemy, &mut game_state.particals);
+    fn boss_part_hit(part: &mut BossPart, damage: u32, score: &mut Score, tick: u64, particals: &mut Arena<Partical>) -> bool {
+        if part.take_damage(damage) {
+            EntityManager::ship_death(part, particals);
+            score.add_kill(part.get_score_value(), part.get_body().pos, tick);
+            part.is_core()
+        } else {
+            false
+        }
+    }
+
+    fn player_killed(state: &mut PlayerState, tick: u64) {
+        state.player.last_death = tick;
+        state.score.reset_combo();
+    }
+
+    fn collect_pickup(game_state: &mut GameState, player: usize, pickup_type: PickupType) {
+        let state = &mut game_state.players[player];
+        match pickup_type {
+            PickupType::WeaponUpgrade => state.player.upgrade_weapon(),
+            PickupType::Shield => state.player.restore_shield(),
+            PickupType::ExtraLife => state.lives += 1,
+            PickupType::Bomb => state.player.bombs += 1,
+            PickupType::SpeedBoost => state.player.add_speed_boost()
+        }
+    }
+
+    fn pickup_collision(game_state: &mut GameState, player: usize, player_col_area: &bounding_volume::AABB<Point<f32, nalgebra::U2>>) {
+        let mut collected = vec![];
+        let collector = &game_state.players[player].player;
+        for pickup in &mut game_state.pickups {
+            if !collector.is_dead() && !pickup.is_dead() && EntityManager::is_col_area_entity_collision(&game_state.sprite_hulls, player_col_area, collector, pickup) {
+                pickup.set_dead();
+                collected.push(pickup.get_pickup_type());
+            }
+        }
+        for pickup_type in collected {
+            EntityManager::collect_pickup(game_state, player, pickup_type);
+        }
+    }
+
+    fn player_collision(game_state: &mut GameState, player: usize, spawned_enemies: &mut Vec<Enemy>) {
+        if !EntityManager::is_player_alive(game_state, player) {
+            return;
+        }
+        let sprite_hulls = game_state.sprite_hulls.clone();
+        let player_col_area = match EntityManager::create_entity_collision_area(&sprite_hulls, &game_state.players[player].player) {
+            Some(player_col_area) => player_col_area,
+            None => return
+        };
+        let tick = game_state.tick;
+        {
+            let state = &mut game_state.players[player];
+            for enemy in &mut game_state.enemies {
+                if state.player.is_vulnerable() && !enemy.is_dead() && EntityManager::is_col_area_entity_collision(&sprite_hulls, &player_col_area, &state.player, enemy) {
+                    //Player hit by ship
+                    let enemy_hit_points = enemy.get_unit().hit_points;
+                    enemy.take_damage(enemy_hit_points);
+                    EntityManager::enemy_death(enemy, &mut game_state.particals, spawned_enemies, &mut game_state.pickups, &mut game_state.rng);
+                    if state.player.take_damage(RAM_DAMAGE) {
+                        EntityManager::ship_death(&mut state.player, &mut game_state.particals);
+                        EntityManager::player_killed(state, tick);
+                    }
+                }
+            }
+        }
+        EntityManager::pickup_collision(game_state, player, &player_col_area);
+        let state = &mut game_state.players[player];
+        if let Some(ref mut boss) = game_state.boss {
+            for part in boss.get_parts_mut() {
+                if state.player.is_vulnerable() && !part.is_dead() && EntityManager::is_col_area_entity_collision(&sprite_hulls, &player_col_area, &state.player, part) {
+                    //Player hit by boss
+                    if state.player.take_damage(RAM_DAMAGE) {
+                        EntityManager::ship_death(&mut state.player, &mut game_state.particals);
+                        EntityManager::player_killed(state, tick);
+                    }
+                }
+            }
+        }
+    }
+
+    //Capsule from the ship to the right edge of the player area
+    fn create_beam_body(origin: Vector2<f32>, length: f32) -> Body {
+        let mut body = Body::new(origin.x + length / 2.0, origin.y, length, BEAM_HALF_WIDTH * 2.0, 1.0, 1.0, f32::consts::PI / 2.0, true);
+        body.set_shape(CollisionShape::Capsule(length / 2.0, BEAM_HALF_WIDTH));
+        body
+    }
+
+    //A held laser damages the nearest thing in front of the ship every tick and stops there,
+    //returns whether that destroyed the boss core
+    fn player_beam_collision(game_state: &mut GameState, player: usize, nearby_enemies: &mut Vec<EntityId>, spawned_enemies: &mut Vec<Enemy>) -> bool {
+        let origin = game_state.players[player].player.get_body().pos;
+        if !game_state.players[player].player.is_beam_firing() {
+            game_state.players[player].beam_length = None;
+            return false;
+        }
+        let player_area = game_state.play_space.player_area;
+        let max_length = (player_area.x + player_area.w - origin.x).max(0.0);
+        let beam = EntityManager::create_beam_body(origin, max_length);
+        let beam_area = bounding_volume::AABB::new(
+            Point2::new(origin.x, origin.y - BEAM_HALF_WIDTH),
+            Point2::new(origin.x + max_length, origin.y + BEAM_HALF_WIDTH)
+        );
+        let sprite_hulls = game_state.sprite_hulls.clone();
+        let mut nearest: Option<(f32, ProjectileTarget)> = None;
+        {
+            let mut consider_hit = |pos: Vector2<f32>, target: ProjectileTarget| {
+                let distance = (pos.x - origin.x).max(0.0);
+                if nearest.as_ref().map_or(true, |hit| distance < hit.0) {
+                    nearest = Some((distance, target));
+                }
+            };
+            game_state.enemy_grid.query(&beam_area, nearby_enemies);
+            for &id in nearby_enemies.iter() {
+                if let Some(enemy) = game_state.enemies.get(id) {
+                    if !enemy.is_dead() && is_body_collision(&sprite_hulls, &beam, &enemy.get_unit().body) {
+                        consider_hit(enemy.get_unit().body.pos, ProjectileTarget::Enemy(id));
                     }
                 }
             }
+            if let Some(ref boss) = game_state.boss {
+                for (i, part) in boss.get_parts().iter().enumerate() {
+                    let body = &part.get_unit().body;
+                    if !part.is_dead() && body.collidable && is_body_collision(&sprite_hulls, &beam, body) {
+                        consider_hit(body.pos, ProjectileTarget::BossPart(i));
+                    }
+                }
+            }
+        }
+        game_state.players[player].beam_length = Some(nearest.as_ref().map_or(max_length, |hit| hit.0));
+        let score = &mut game_state.players[player].score;
+        match nearest {
+            Some((_, ProjectileTarget::Enemy(id))) => {
+                if let Some(enemy) = game_state.enemies.get_mut(id) {
+                    EntityManager::enemy_hit(enemy, BEAM_DAMAGE, score, game_state.tick, &mut game_state.particals, spawned_enemies, &mut game_state.pickups, &mut game_state.rng);
+                }
+                false
+            },
+            Some((_, ProjectileTarget::BossPart(i))) => match game_state.boss {
+                Some(ref mut boss) => EntityManager::boss_part_hit(&mut boss.get_parts_mut()[i], BEAM_DAMAGE, score, game_state.tick, &mut game_state.particals),
+                None => false
+            },
+            _ => false
+        }
+    }
 
-            for projectile in game_state.projectiles.iter_mut(){
-                if let Some(projectile_col_area) = EntityManager::create_entity_collision_area(projectile) {
+    pub fn collision_resolution(game_state: &mut GameState) {
+        let mut spawned_enemies = vec![];
+        for player in 0..game_state.players.len() {
+            EntityManager::player_collision(game_state, player, &mut spawned_enemies);
+        }
+
+        //Enemy areas are built once per tick and bucketed so each projectile only tests its neighbours
+        let sprite_hulls = game_state.sprite_hulls.clone();
+        game_state.enemy_grid.update(game_state.enemies.iter()
+            .map(|&(id, ref enemy)| (id, EntityManager::create_entity_swept_area(&sprite_hulls, enemy))));
+        let mut nearby_enemies = vec![];
+
+        let mut boss_core_destroyed = false;
+        for player in 0..game_state.players.len() {
+            boss_core_destroyed |= EntityManager::player_beam_collision(game_state, player, &mut nearby_enemies, &mut spawned_enemies);
+        }
+        for projectile in game_state.projectiles.values_mut(){
+            if projectile.is_dead() {
+                continue;
+            }
+            if let Some(projectile_sweep) = EntityManager::create_entity_swept_area(&sprite_hulls, projectile) {
+                //A projectile only hits once, so it goes to whatever it reached first during the tick
+                let mut first_hit: Option<(f32, ProjectileTarget)> = None;
+                {
+                    let mut consider_hit = |time_of_impact: Option<f32>, target: ProjectileTarget| {
+                        if let Some(time_of_impact) = time_of_impact {
+                            if first_hit.as_ref().map_or(true, |hit| time_of_impact < hit.0) {
+                                first_hit = Some((time_of_impact, target));
+                            }
+                        }
+                    };
                     if projectile.is_player_owned() {
-                        for enemy in &mut game_state.enemies {
-                            if EntityManager::is_col_area_entity_collision(&projectile_col_area, enemy) {
-                                //Enemy hit by projectile
-                                EntityManager::ship_hit_by_projectile(enemy, projectile, &mut game_state.particals);
-                                game_state.score += 150;
+                        game_state.enemy_grid.query(&projectile_sweep, &mut nearby_enemies);
+                        for &id in &nearby_enemies {
+                            if let Some(enemy) = game_state.enemies.get(id) {
+                                if !enemy.is_dead() {
+                                    consider_hit(EntityManager::get_time_of_impact(&sprite_hulls, projectile, enemy), ProjectileTarget::Enemy(id));
+                                }
+                            }
+                        }
+                        if let Some(ref boss) = game_state.boss {
+                            for (i, part) in boss.get_parts().iter().enumerate() {
+                                if !part.is_dead() && EntityManager::is_swept_area_entity_collision(&sprite_hulls, &projectile_sweep, part) {
+                                    consider_hit(EntityManager::get_time_of_impact(&sprite_hulls, projectile, part), ProjectileTarget::BossPart(i));
+                                }
                             }
                         }
                     } else {
-                        if projectile_col_area.intersects(&player_col_area) {
-                            //Player hit by projectile
-                            EntityManager::ship_hit_by_projectile(&mut game_state.player, projectile, &mut game_state.particals);
+                        for (i, state) in game_state.players.iter().enumerate() {
+                            if state.player.is_vulnerable() && EntityManager::is_swept_area_entity_collision(&sprite_hulls, &projectile_sweep, &state.player) {
+                                consider_hit(EntityManager::get_time_of_impact(&sprite_hulls, projectile, &state.player), ProjectileTarget::Player(i));
+                            }
                         }
                     }
                 }
+                match first_hit {
+                    Some((_, ProjectileTarget::Enemy(id))) => {
+                        //Enemy hit by projectile
+                        projectile.set_dead();
+                        let score = &mut game_state.players[projectile.get_owner()].score;
+                        if let Some(enemy) = game_state.enemies.get_mut(id) {
+                            EntityManager::enemy_hit(enemy, projectile.get_damage(), score, game_state.tick, &mut game_state.particals, &mut spawned_enemies, &mut game_state.pickups, &mut game_state.rng);
+                        }
+                    },
+                    Some((_, ProjectileTarget::BossPart(i))) => {
+                        //Boss part hit by projectile
+                        projectile.set_dead();
+                        let score = &mut game_state.players[projectile.get_owner()].score;
+                        if let Some(ref mut boss) = game_state.boss {
+                            boss_core_destroyed |= EntityManager::boss_part_hit(&mut boss.get_parts_mut()[i], projectile.get_damage(), score, game_state.tick, &mut game_state.particals);
+                        }
+                    },
+                    Some((_, ProjectileTarget::Player(i))) => {
+                        //Player hit by projectile
+                        let state = &mut game_state.players[i];
+                        if EntityManager::ship_hit_by_projectile(&mut state.player, projectile, &mut game_state.particals) {
+                            EntityManager::player_killed(state, game_state.tick);
+                        }
+                    },
+                    None => ()
+                }
+            }
+        }
+        if boss_core_destroyed {
+            EntityManager::start_boss_defeat(game_state);
+        }
+        game_state.enemies.extend(spawned_enemies);
+    }
+
+    fn start_boss_defeat(game_state: &mut GameState) {
+        if let Some(ref mut boss) = game_state.boss {
+            boss.start_defeat();
+            for projectile in game_state.projectiles.values_mut() {
+                if !projectile.is_player_owned() {
+                    projectile.set_dead();
+                }
             }
         }
     }
@@ -150,50 +572,86 @@ impl EntityManager {
         let play_space = game_state.play_space.clone();
         game_state.enemies.retain(|enemy| EntityManager::retain_entity(&play_space, enemy));
         game_state.particals.retain(|partical| EntityManager::retain_entity(&play_space, partical));
+        game_state.pickups.retain(|pickup| EntityManager::retain_entity(&play_space, pickup));
+        let missed_owners: Vec<usize> = game_state.projectiles.values()
+            .filter(|projectile| projectile.is_player_owned() && !projectile.is_dead() && !EntityManager::retain_entity(&play_space, *projectile))
+            .map(|projectile| projectile.get_owner())
+            .collect();
+        for owner in missed_owners {
+            game_state.players[owner].score.register_miss();
+        }
         game_state.projectiles.retain(|projectile| EntityManager::retain_entity(&play_space, projectile));
     }
 
-    fn is_col_area_entity_collision(col_area: &bounding_volume::AABB<Point<f32, nalgebra::U2>>, entity: &Enemy) -> bool {
-        if let Some(entity_col_area) = EntityManager::create_entity_collision_area(entity) {
+    fn is_col_area_entity_collision(sprite_hulls: &SpriteHulls, col_area: &bounding_volume::AABB<Point<f32, nalgebra::U2>>, collider: &Entity, entity: &Entity) -> bool {
+        if let Some(entity_col_area) = EntityManager::create_entity_collision_area(sprite_hulls, entity) {
             if col_area.intersects(&entity_col_area) {
-                return true;
+                return is_body_collision(sprite_hulls, &collider.get_unit().body, &entity.get_unit().body);
             }
         }
         false
     }
 
-    pub fn draw(game_state: &GameState, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        for projectile in game_state.projectiles.iter(){
-            projectile.draw(asset_manager, ctx, interpolation_value, camera);
+    pub fn player_fire(game_state: &mut GameState, player: usize) {
+        if EntityManager::is_player_alive(game_state, player) {
+            game_state.players[player].player.pull_trigger();
         }
-        for enemy in &game_state.enemies {
-            enemy.draw(asset_manager, ctx, interpolation_value, camera);
-        }
-        game_state.player.draw(asset_manager, ctx, interpolation_value, camera);
+    }
 
-        for partical in &game_state.particals {
-            partical.draw(asset_manager, ctx, interpolation_value, camera);
-        }
+    pub fn player_fire_cancel(game_state: &mut GameState, player: usize) {
+        game_state.players[player].player.release_trigger();
+    }
+
+    fn is_on_screen(play_space: &PlaySpace, entity: &Entity) -> bool {
+        let pos = entity.get_body().pos;
+        pos.x >= 0.0 && pos.x <= play_space.player_area.w && pos.y >= 0.0 && pos.y <= play_space.player_area.h
     }
 
-    pub fn player_fire(game_state: &mut GameState) {
-        if EntityManager::is_player_alive(game_state) {
-            let player_body = game_state.player.get_body();
-            game_state.projectiles.push(
-                Projectile::new(player_body.pos.x, player_body.pos.y, true)
-            );
+    pub fn player_bomb(game_state: &mut GameState, player: usize) {
+        if !EntityManager::is_player_alive(game_state, player) || game_state.players[player].player.bombs == 0 {
+            return;
         }
+        game_state.players[player].player.bombs -= 1;
+        let play_space = game_state.play_space.clone();
+        for projectile in game_state.projectiles.values_mut() {
+            if !projectile.is_player_owned() && EntityManager::is_on_screen(&play_space, projectile) {
+                projectile.set_dead();
+            }
+        }
+        let mut spawned_enemies = vec![];
+        for enemy in &mut game_state.enemies {
+            if !enemy.is_dead() && EntityManager::is_on_screen(&play_space, enemy) {
+                EntityManager::enemy_hit(enemy, BOMB_DAMAGE, &mut game_state.players[player].score, game_state.tick, &mut game_state.particals, &mut spawned_enemies, &mut game_state.pickups, &mut game_state.rng);
+            }
+        }
+        game_state.enemies.extend(spawned_enemies);
+        let mut boss_core_destroyed = false;
+        if let Some(ref mut boss) = game_state.boss {
+            for part in boss.get_parts_mut() {
+                if !part.is_dead() && part.get_body().collidable {
+                    boss_core_destroyed |= EntityManager::boss_part_hit(part, BOMB_DAMAGE, &mut game_state.players[player].score, game_state.tick, &mut game_state.particals);
+                }
+            }
+        }
+        if boss_core_destroyed {
+            EntityManager::start_boss_defeat(game_state);
+        }
+        game_state.particals.insert(Partical::new_bomb_flash(play_space.player_area.w / 2.0, play_space.player_area.h / 2.0));
     }
 
-    pub fn player_move(game_state: &mut GameState, dir: u16) {
-        if EntityManager::is_player_alive(game_state) {
-            game_state.player.move_dir(dir);
+    pub fn player_move(game_state: &mut GameState, player: usize, dir: Direction) {
+        if EntityManager::is_player_alive(game_state, player) {
+            game_state.players[player].player.move_dir(dir);
         }
     }
 
-    pub fn player_move_cancel(game_state: &mut GameState, dir: u16) {
-        if EntityManager::is_player_alive(game_state) {
-            game_state.player.move_dir_cancel(dir);
+    pub fn player_move_cancel(game_state: &mut GameState, player: usize, dir: Direction) {
+        if EntityManager::is_player_alive(game_state, player) {
+            game_state.players[player].player.move_dir_cancel(dir);
         }
     }
-}
\ No newline at end of file
+
+    pub fn player_analog_move(game_state: &mut GameState, player: usize, x: f32, y: f32) {
+        game_state.players[player].player.set_analog_dir(x, y);
+    }
+}
@@ -0,0 +1,101 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::rc::Rc;
+use rand::{SeedableRng, XorShiftRng};
+use nalgebra::Vector2;
+use super::play_space::*;
+use super::player::*;
+use super::enemy::*;
+use super::boss::*;
+use super::projectile::*;
+use super::particals::*;
+use super::pickup::*;
+use super::score::*;
+use super::arena::Arena;
+use super::spatial_grid::SpatialGrid;
+use super::collision_shape::SpriteHulls;
+
+const STARTING_LIVES: i32 = 10;
+const RNG_SEED_SALT: [u32; 3] = [0x9E37_79B9, 0x85EB_CA6B, 0x2545_F491];
+pub const MAX_PLAYERS: usize = 2;
+const COLLISION_GRID_CELL_SIZE: f32 = 96.0;
+
+pub struct PlayerState {
+    pub player: Player,
+    pub lives: i32,
+    pub score: Score,
+    pub beam_length: Option<f32> //how far the laser reached this tick, None while it's off
+}
+
+impl PlayerState {
+    pub fn is_out(&self) -> bool {
+        self.lives <= 0
+    }
+}
+
+pub struct GameState {
+    pub tick: u64,
+    pub seed: u32,
+    pub rng: XorShiftRng,
+    pub players: Vec<PlayerState>,
+    pub play_space: PlaySpace,
+    pub projectiles: Arena<Projectile>,
+    pub enemies: Arena<Enemy>,
+    pub boss: Option<Boss>,
+    pub particals: Arena<Partical>,
+    pub pickups: Vec<Pickup>,
+    pub enemy_grid: SpatialGrid,
+    pub sprite_hulls: Rc<SpriteHulls>
+}
+
+impl GameState {
+    pub fn new(play_space: PlaySpace, seed: u32, player_count: usize, sprite_hulls: Rc<SpriteHulls>) -> GameState {
+        debug_assert!(player_count >= 1 && player_count <= MAX_PLAYERS, "player count {} out of range", player_count);
+        let player_spawn_x = play_space.player_area.w / 8.0;
+        let players = (0..player_count)
+            .map(|i| PlayerState {
+                player: Player::new(player_spawn_x, GameState::get_player_lane_y(&play_space, i, player_count)),
+                lives: STARTING_LIVES,
+                score: Score::new(),
+                beam_length: None
+            })
+            .collect();
+        let entity_area = play_space.entity_area;
+        let enemy_grid = SpatialGrid::new(
+            Vector2::new(entity_area.x, entity_area.y - entity_area.h),
+            Vector2::new(entity_area.w, entity_area.h),
+            COLLISION_GRID_CELL_SIZE
+        );
+        GameState {
+            tick: 0,
+            seed,
+            rng: GameState::create_rng(seed),
+            players,
+            play_space,
+            projectiles: Arena::new(),
+            enemies: Arena::new(),
+            boss: None,
+            particals: Arena::new(),
+            pickups: vec![],
+            enemy_grid,
+            sprite_hulls
+        }
+    }
+
+    //Players spawn spread evenly down the screen so co-op ships don't start stacked
+    pub fn get_player_lane_y(play_space: &PlaySpace, player: usize, player_count: usize) -> f32 {
+        play_space.player_area.h * (player_count - player) as f32 / (player_count + 1) as f32
+    }
+
+    fn create_rng(seed: u32) -> XorShiftRng {
+        XorShiftRng::from_seed([
+            seed ^ RNG_SEED_SALT[0],
+            seed.rotate_left(11) ^ RNG_SEED_SALT[1],
+            seed.rotate_left(22),
+            RNG_SEED_SALT[2]
+        ])
+    }
+}
@@ -0,0 +1,86 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use super::simulation::*;
+use super::input::*;
+use super::replay::*;
+
+const AUTOPILOT_FIRE_INTERVAL: u64 = 10;
+const AUTOPILOT_CONFIRM_INTERVAL: u64 = 60;
+
+pub struct ScriptedInput {
+    pub tick: u64,
+    pub input: SimulationInput
+}
+
+pub struct HeadlessResult {
+    pub seed: u32,
+    pub ticks: u64,
+    pub scores: Vec<u32>,
+    pub lives: Vec<i32>,
+    pub wave_level: u32,
+    pub game_over: bool
+}
+
+pub struct HeadlessRunner {
+    simulation: Simulation,
+    script: Vec<ScriptedInput>,
+    max_ticks: u64
+}
+
+impl HeadlessRunner {
+    pub fn new(simulation: Simulation, mut script: Vec<ScriptedInput>, max_ticks: u64) -> HeadlessRunner {
+        script.sort_by_key(|scripted_input| scripted_input.tick);
+        HeadlessRunner {
+            simulation,
+            script,
+            max_ticks
+        }
+    }
+
+    pub fn create_autopilot_script(max_ticks: u64, player_count: usize) -> Vec<ScriptedInput> {
+        let mut script = vec![];
+        for tick in 0..max_ticks {
+            if tick % AUTOPILOT_CONFIRM_INTERVAL == 0 {
+                script.push(ScriptedInput { tick, input: SimulationInput::Confirm });
+            }
+            if tick % AUTOPILOT_FIRE_INTERVAL == 0 {
+                for player in 0..player_count {
+                    script.push(ScriptedInput { tick, input: SimulationInput::PlayerFire(player) });
+                }
+            }
+        }
+        script
+    }
+
+    pub fn create_replay_script(replay: &Replay) -> Vec<ScriptedInput> {
+        replay.events.iter()
+            .flat_map(|event| event.input.get_simulation_inputs()
+                .into_iter()
+                .map(move |input| ScriptedInput { tick: event.tick, input }))
+            .collect()
+    }
+
+    pub fn run(&mut self) -> HeadlessResult {
+        let mut next_input = 0;
+        let mut steps = 0;
+        while steps < self.max_ticks && !self.simulation.is_game_over() {
+            while next_input < self.script.len() && self.script[next_input].tick <= self.simulation.game_state.tick {
+                self.simulation.apply_input(self.script[next_input].input);
+                next_input += 1;
+            }
+            self.simulation.step();
+            steps += 1;
+        }
+        HeadlessResult {
+            seed: self.simulation.game_state.seed,
+            ticks: self.simulation.game_state.tick,
+            scores: self.simulation.game_state.players.iter().map(|state| state.score.get_total()).collect(),
+            lives: self.simulation.game_state.players.iter().map(|state| state.lives).collect(),
+            wave_level: self.simulation.wave_manager.get_wave_level(),
+            game_over: self.simulation.is_game_over()
+        }
+    }
+}
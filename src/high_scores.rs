@@ -0,0 +1,165 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use dirs;
+use toml;
+
+const MAX_ENTRIES: usize = 10;
+const INITIALS_LENGTH: usize = 3;
+const SECONDS_PER_DAY: u64 = 86400;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    pub wave: u32,
+    pub date: String
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct HighScoreTable {
+    entries: Vec<HighScoreEntry>
+}
+
+fn invalid_data<E: ToString>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+impl HighScoreTable {
+    fn get_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|data_dir| data_dir.join("rust_arcade_shooter").join("high_scores.toml"))
+    }
+
+    pub fn load() -> io::Result<HighScoreTable> {
+        let path = match HighScoreTable::get_path() {
+            Some(path) => path,
+            None => return Ok(HighScoreTable::default())
+        };
+        let mut contents = String::new();
+        match File::open(&path) {
+            Ok(mut file) => file.read_to_string(&mut contents)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(HighScoreTable::default()),
+            Err(e) => return Err(e)
+        };
+        let mut table: HighScoreTable = toml::from_str(&contents).map_err(invalid_data)?;
+        table.sort();
+        Ok(table)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match HighScoreTable::get_path() {
+            Some(path) => path,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "no user data directory"))
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string(self).map_err(invalid_data)?;
+        File::create(&path)?.write_all(contents.as_bytes())
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn get_entries(&self) -> &Vec<HighScoreEntry> {
+        &self.entries
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) {
+        //Ties keep the older entry ahead
+        let position = self.entries.iter().position(|existing| entry.score > existing.score).unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+pub fn get_current_date() -> String {
+    let days = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => (since_epoch.as_secs() / SECONDS_PER_DAY) as i64,
+        Err(_) => 0
+    };
+    //Days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub struct NameEntry {
+    pub score: u32,
+    pub wave: u32,
+    letters: [u8; INITIALS_LENGTH],
+    cursor: usize
+}
+
+impl NameEntry {
+    pub fn new(score: u32, wave: u32) -> NameEntry {
+        NameEntry {
+            score,
+            wave,
+            letters: [b'A'; INITIALS_LENGTH],
+            cursor: 0
+        }
+    }
+
+    pub fn get_initials(&self) -> String {
+        self.letters.iter().map(|letter| *letter as char).collect()
+    }
+
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn next_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'Z' { b'A' } else { *letter + 1 };
+    }
+
+    pub fn previous_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'A' { b'Z' } else { *letter - 1 };
+    }
+
+    pub fn next_slot(&mut self) {
+        self.cursor = (self.cursor + 1).min(INITIALS_LENGTH - 1);
+    }
+
+    pub fn previous_slot(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn is_last_slot(&self) -> bool {
+        self.cursor == INITIALS_LENGTH - 1
+    }
+
+    pub fn create_entry(&self) -> HighScoreEntry {
+        HighScoreEntry {
+            initials: self.get_initials(),
+            score: self.score,
+            wave: self.wave,
+            date: get_current_date()
+        }
+    }
+}
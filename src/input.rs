@@ -0,0 +1,237 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use ggez::event::Button;
+use super::game_state::MAX_PLAYERS;
+
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Bomb,
+    Pause
+}
+
+pub const ACTIONS: [Action; 7] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Fire,
+    Action::Bomb,
+    Action::Pause
+];
+
+impl Action {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Bomb => "bomb",
+            Action::Pause => "pause"
+        }
+    }
+
+    pub fn get_label(&self) -> &'static str {
+        match *self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Fire => "Fire",
+            Action::Bomb => "Bomb",
+            Action::Pause => "Pause"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|action| action.get_name() == name).cloned()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Right,
+    Left
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimulationInput {
+    PlayerMove(usize, Direction),
+    PlayerMoveCancel(usize, Direction),
+    PlayerAnalogMove(usize, f32, f32),
+    PlayerFire(usize),
+    PlayerFireCancel(usize),
+    PlayerBomb(usize),
+    Confirm
+}
+
+impl SimulationInput {
+    pub fn get_player(&self) -> Option<usize> {
+        match *self {
+            SimulationInput::PlayerMove(player, _) |
+            SimulationInput::PlayerMoveCancel(player, _) |
+            SimulationInput::PlayerAnalogMove(player, _, _) |
+            SimulationInput::PlayerFire(player) |
+            SimulationInput::PlayerFireCancel(player) |
+            SimulationInput::PlayerBomb(player) => Some(player),
+            SimulationInput::Confirm => None
+        }
+    }
+}
+
+pub fn from_action(player: usize, action: Action, pressed: bool, repeat: bool) -> Vec<SimulationInput> {
+    if pressed {
+        from_action_pressed(player, action, repeat)
+    } else {
+        from_action_released(player, action)
+    }
+}
+
+fn from_action_pressed(player: usize, action: Action, repeat: bool) -> Vec<SimulationInput> {
+    match action {
+        Action::MoveUp => vec![SimulationInput::PlayerMove(player, Direction::Up)],
+        Action::MoveDown => vec![SimulationInput::PlayerMove(player, Direction::Down)],
+        Action::MoveRight => vec![SimulationInput::PlayerMove(player, Direction::Right)],
+        Action::MoveLeft => vec![SimulationInput::PlayerMove(player, Direction::Left)],
+        Action::Fire => if !repeat {
+            vec![SimulationInput::PlayerFire(player)]
+        } else {
+            vec![]
+        },
+        Action::Bomb => if !repeat {
+            vec![SimulationInput::PlayerBomb(player)]
+        } else {
+            vec![]
+        },
+        Action::Pause => vec![]
+    }
+}
+
+fn from_action_released(player: usize, action: Action) -> Vec<SimulationInput> {
+    match action {
+        Action::MoveUp => vec![SimulationInput::PlayerMoveCancel(player, Direction::Up)],
+        Action::MoveDown => vec![SimulationInput::PlayerMoveCancel(player, Direction::Down)],
+        Action::MoveRight => vec![SimulationInput::PlayerMoveCancel(player, Direction::Right)],
+        Action::MoveLeft => vec![SimulationInput::PlayerMoveCancel(player, Direction::Left)],
+        Action::Fire => vec![SimulationInput::PlayerFireCancel(player), SimulationInput::Confirm],
+        _ => vec![]
+    }
+}
+
+pub fn from_button(button: Button) -> Option<Action> {
+    match button {
+        Button::DPadUp => Some(Action::MoveUp),
+        Button::DPadDown => Some(Action::MoveDown),
+        Button::DPadLeft => Some(Action::MoveLeft),
+        Button::DPadRight => Some(Action::MoveRight),
+        Button::A | Button::RightShoulder => Some(Action::Fire),
+        Button::B | Button::LeftShoulder => Some(Action::Bomb),
+        Button::Start => Some(Action::Pause),
+        _ => None
+    }
+}
+
+fn clamp(value: f32, min: f32, max: f32) -> f32 {
+    value.max(min).min(max)
+}
+
+//SDL axes run from i16::MIN to i16::MAX, so the negative end would reach just past -1 unclamped
+pub fn normalize_axis(value: i16) -> f32 {
+    clamp(value as f32 / i16::max_value() as f32, -1.0, 1.0)
+}
+
+//Raw stick axes are -1..1 with y pointing down, the result points up like the world
+pub fn apply_stick_dead_zone(x: f32, y: f32) -> (f32, f32) {
+    let magnitude = (x.powi(2) + y.powi(2)).sqrt();
+    if magnitude <= STICK_DEAD_ZONE {
+        return (0.0, 0.0);
+    }
+    let scale = clamp((magnitude - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE), 0.0, 1.0) / magnitude;
+    (x * scale, -y * scale)
+}
+
+//SDL instance ids keep counting up each time a pad is plugged in, so each pad is given the lowest
+//free controller slot when it connects and holds it until it's unplugged
+pub struct ControllerSlots {
+    slots: [Option<i32>; MAX_PLAYERS]
+}
+
+impl ControllerSlots {
+    pub fn new() -> ControllerSlots {
+        ControllerSlots {
+            slots: [None; MAX_PLAYERS]
+        }
+    }
+
+    pub fn get_slot(&self, instance_id: i32) -> Option<usize> {
+        self.slots.iter().position(|slot| *slot == Some(instance_id))
+    }
+
+    //None once every slot is taken, extra pads are ignored
+    pub fn connect(&mut self, instance_id: i32) -> Option<usize> {
+        if let Some(slot) = self.get_slot(instance_id) {
+            return Some(slot);
+        }
+        let slot = self.slots.iter().position(|slot| slot.is_none())?;
+        self.slots[slot] = Some(instance_id);
+        Some(slot)
+    }
+
+    pub fn disconnect(&mut self, instance_id: i32) {
+        if let Some(slot) = self.get_slot(instance_id) {
+            self.slots[slot] = None;
+        }
+    }
+
+    pub fn get_instance_ids(&self) -> Vec<i32> {
+        self.slots.iter().filter_map(|slot| *slot).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_are_clamped_to_one() {
+        assert_eq!(normalize_axis(i16::min_value()), -1.0);
+        assert_eq!(normalize_axis(i16::max_value()), 1.0);
+        assert_eq!(normalize_axis(0), 0.0);
+    }
+
+    #[test]
+    fn reconnected_pad_takes_the_freed_slot() {
+        let mut slots = ControllerSlots::new();
+        assert_eq!(slots.connect(0), Some(0));
+        assert_eq!(slots.connect(1), Some(1));
+        assert_eq!(slots.connect(0), Some(0));
+        slots.disconnect(0);
+        assert_eq!(slots.get_slot(0), None);
+        //Plugging the first pad back in gives it a new instance id
+        assert_eq!(slots.connect(2), Some(0));
+        assert_eq!(slots.get_slot(1), Some(1));
+        assert_eq!(slots.get_instance_ids(), vec![2, 1]);
+    }
+
+    #[test]
+    fn pads_past_the_player_count_get_no_slot() {
+        let mut slots = ControllerSlots::new();
+        for instance_id in 0..MAX_PLAYERS as i32 {
+            assert!(slots.connect(instance_id).is_some());
+        }
+        assert_eq!(slots.connect(MAX_PLAYERS as i32), None);
+    }
+}
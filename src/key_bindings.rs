@@ -0,0 +1,155 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use ggez::event::Keycode;
+use dirs;
+use toml;
+use super::input::*;
+use super::game_state::MAX_PLAYERS;
+
+type ActionBindings = Vec<(Action, Vec<Keycode>)>;
+
+pub struct KeyBindings {
+    players: Vec<ActionBindings>
+}
+
+fn invalid_data<E: ToString>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+fn get_player_name(player: usize) -> String {
+    format!("player{}", player + 1)
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            players: vec![
+                vec![
+                    (Action::MoveUp, vec![Keycode::W, Keycode::Up]),
+                    (Action::MoveDown, vec![Keycode::S, Keycode::Down]),
+                    (Action::MoveLeft, vec![Keycode::A, Keycode::Left]),
+                    (Action::MoveRight, vec![Keycode::D, Keycode::Right]),
+                    (Action::Fire, vec![Keycode::Space]),
+                    (Action::Bomb, vec![Keycode::B]),
+                    (Action::Pause, vec![Keycode::Escape])
+                ],
+                vec![
+                    (Action::MoveUp, vec![Keycode::I]),
+                    (Action::MoveDown, vec![Keycode::K]),
+                    (Action::MoveLeft, vec![Keycode::J]),
+                    (Action::MoveRight, vec![Keycode::L]),
+                    (Action::Fire, vec![Keycode::U]),
+                    (Action::Bomb, vec![Keycode::O]),
+                    (Action::Pause, vec![Keycode::P])
+                ]
+            ]
+        }
+    }
+}
+
+impl KeyBindings {
+    fn get_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join("rust_arcade_shooter").join("bindings.toml"))
+    }
+
+    //Actions missing from the file keep their default keys
+    pub fn load() -> io::Result<KeyBindings> {
+        let mut key_bindings = KeyBindings::default();
+        let path = match KeyBindings::get_path() {
+            Some(path) => path,
+            None => return Ok(key_bindings)
+        };
+        let mut contents = String::new();
+        match File::open(&path) {
+            Ok(mut file) => file.read_to_string(&mut contents)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(key_bindings),
+            Err(e) => return Err(e)
+        };
+        let bindings_file: BTreeMap<String, BTreeMap<String, Vec<String>>> = toml::from_str(&contents).map_err(invalid_data)?;
+        for (player_name, player_bindings) in bindings_file {
+            let player = (0..MAX_PLAYERS).find(|player| get_player_name(*player) == player_name)
+                .ok_or_else(|| invalid_data(format!("unknown player '{}'", player_name)))?;
+            for (action_name, key_names) in player_bindings {
+                let action = Action::from_name(&action_name)
+                    .ok_or_else(|| invalid_data(format!("unknown action '{}'", action_name)))?;
+                let mut keycodes = vec![];
+                for key_name in key_names {
+                    keycodes.push(Keycode::from_name(&key_name)
+                        .ok_or_else(|| invalid_data(format!("unknown key '{}' for {}", key_name, action_name)))?);
+                }
+                key_bindings.set_keys(player, action, keycodes);
+            }
+        }
+        Ok(key_bindings)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match KeyBindings::get_path() {
+            Some(path) => path,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "no user config directory"))
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bindings_file: BTreeMap<String, BTreeMap<String, Vec<String>>> = self.players.iter()
+            .enumerate()
+            .map(|(player, player_bindings)| (
+                get_player_name(player),
+                player_bindings.iter()
+                    .map(|&(action, ref keycodes)| (
+                        action.get_name().to_string(),
+                        keycodes.iter().map(|keycode| keycode.name()).collect()
+                    ))
+                    .collect()
+            ))
+            .collect();
+        let contents = toml::to_string(&bindings_file).map_err(invalid_data)?;
+        File::create(&path)?.write_all(contents.as_bytes())
+    }
+
+    fn set_keys(&mut self, player: usize, action: Action, keycodes: Vec<Keycode>) {
+        if let Some(binding) = self.players[player].iter_mut().find(|binding| binding.0 == action) {
+            binding.1 = keycodes;
+        }
+    }
+
+    pub fn get_keys(&self, player: usize, action: Action) -> &[Keycode] {
+        match self.players[player].iter().find(|binding| binding.0 == action) {
+            Some(binding) => &binding.1,
+            None => &[]
+        }
+    }
+
+    pub fn get_actions(&self, keycode: Keycode) -> Vec<(usize, Action)> {
+        self.players.iter()
+            .enumerate()
+            .flat_map(|(player, player_bindings)| player_bindings.iter()
+                .filter(|binding| binding.1.contains(&keycode))
+                .map(move |binding| (player, binding.0)))
+            .collect()
+    }
+
+    //True when the key performs the action for any player
+    pub fn is_bound(&self, keycode: Keycode, action: Action) -> bool {
+        (0..self.players.len()).any(|player| self.get_keys(player, action).contains(&keycode))
+    }
+
+    //A key only ever drives one action, so it is taken off whatever it was bound to before
+    pub fn rebind(&mut self, player: usize, action: Action, keycode: Keycode) {
+        for player_bindings in &mut self.players {
+            for binding in player_bindings.iter_mut() {
+                binding.1.retain(|bound| *bound != keycode);
+            }
+        }
+        self.set_keys(player, action, vec![keycode]);
+    }
+}
@@ -7,31 +7,89 @@ extern crate ggez;
 extern crate ncollide;
 extern crate nalgebra;
 extern crate rand;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+extern crate dirs;
+extern crate image;
 
 use std::env;
+use std::rc::Rc;
 use std::path;
 use ggez::*;
 use ggez::conf::*;
 
 mod game_event_handler;
+mod game_state;
+mod scene;
+mod scenes;
+mod menu;
+mod simulation;
+mod headless;
+mod renderer;
+mod input;
+mod key_bindings;
+mod replay;
 mod body;
 mod entity;
 mod entity_manager;
 mod asset_manager;
 mod player;
 mod enemy;
+mod enemy_weapon;
+mod player_weapon;
+mod boss;
 mod camera;
 mod projectile;
 mod particals;
+mod pickup;
 mod unit;
 mod wave_manager;
+mod wave_definition;
 mod play_space;
+mod arena;
+mod spatial_grid;
+mod collision_shape;
+mod benchmark;
+mod score;
+mod high_scores;
 
 const WINDOW_W: u32 = 640;
 const WINDOW_H: u32 = 480;
+const HEADLESS_DEFAULT_TICKS: u64 = 60 * 60 * 10;
+
+fn get_assets_path() -> Option<path::PathBuf> {
+    env::var("CARGO_MANIFEST_DIR").ok().map(|manifest_dir| {
+        let mut path = path::PathBuf::from(manifest_dir);
+        path.push("assets");
+        path
+    })
+}
+
+fn load_wave_set() -> Result<wave_definition::WaveSet, wave_definition::WaveLoadError> {
+    match get_assets_path() {
+        Some(mut path) => {
+            path.push("waves.toml");
+            wave_definition::WaveSet::load(&path)
+        },
+        None => Ok(wave_definition::WaveSet::default())
+    }
+}
+
+//Missing or unreadable sprites only cost the hulls, entities fall back to their declared shapes
+fn load_sprite_hulls() -> collision_shape::SpriteHulls {
+    match get_assets_path() {
+        Some(path) => collision_shape::SpriteHulls::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to build sprite collision hulls: {}", e);
+            collision_shape::SpriteHulls::default()
+        }),
+        None => collision_shape::SpriteHulls::default()
+    }
+}
 
 fn get_context_builder() -> Option<ContextBuilder> {
-    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+    if let Some(path) = get_assets_path() {
         let mut cb = ContextBuilder::new("RustArcadeShooter", "Infinity")
             .window_setup(conf::WindowSetup::default().title("Rust Arcade Shooter"))
             .window_mode(conf::WindowMode::default()
@@ -39,8 +97,6 @@ fn get_context_builder() -> Option<ContextBuilder> {
                 .fullscreen_type(FullscreenType::Off)
                 .vsync(false)
             );
-        let mut path = path::PathBuf::from(manifest_dir);
-        path.push("assets");
         cb = cb.add_resource_path(path);
 
         return Some(cb);
@@ -48,11 +104,98 @@ fn get_context_builder() -> Option<ContextBuilder> {
     None
 }
 
+fn get_arg_value(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+fn get_seed(args: &[String]) -> u32 {
+    get_arg_value(args, "--seed")
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(simulation::Simulation::create_seed)
+}
+
+fn get_player_count(args: &[String]) -> Option<usize> {
+    let players = match get_arg_value(args, "--players") {
+        Some(players) => players,
+        None => return Some(1)
+    };
+    match players.parse() {
+        Ok(player_count) if player_count >= 1 && player_count <= game_state::MAX_PLAYERS => Some(player_count),
+        _ => {
+            eprintln!("--players must be between 1 and {}, got {}", game_state::MAX_PLAYERS, players);
+            None
+        }
+    }
+}
+
+fn load_replay(args: &[String]) -> Option<replay::Replay> {
+    if let Some(replay_path) = get_arg_value(args, "--replay") {
+        match replay::Replay::load(path::Path::new(&replay_path)) {
+            Ok(replay) => return Some(replay),
+            Err(e) => eprintln!("Failed to load replay {}: {}", replay_path, e)
+        }
+    }
+    None
+}
+
+fn get_replay_mode(args: &[String]) -> replay::ReplayMode {
+    if let Some(replay) = load_replay(args) {
+        return replay::ReplayMode::Playback(replay::ReplayPlayer::new(replay));
+    }
+    if let Some(record_path) = get_arg_value(args, "--record") {
+        match replay::ReplayRecorder::create(path::Path::new(&record_path), get_seed(args)) {
+            Ok(recorder) => return replay::ReplayMode::Record(recorder),
+            Err(e) => eprintln!("Failed to create replay {}: {}", record_path, e)
+        }
+    }
+    replay::ReplayMode::Off
+}
+
+fn run_headless(args: &[String], wave_set: wave_definition::WaveSet, sprite_hulls: Rc<collision_shape::SpriteHulls>) {
+    let max_ticks = get_arg_value(args, "--ticks")
+        .and_then(|ticks| ticks.parse().ok())
+        .unwrap_or(HEADLESS_DEFAULT_TICKS);
+    let (seed, player_count, script) = match load_replay(args) {
+        Some(replay) => (replay.seed, replay.player_count, headless::HeadlessRunner::create_replay_script(&replay)),
+        None => {
+            let player_count = match get_player_count(args) {
+                Some(player_count) => player_count,
+                None => return
+            };
+            (get_seed(args), player_count, headless::HeadlessRunner::create_autopilot_script(max_ticks, player_count))
+        }
+    };
+    let simulation = simulation::Simulation::new(WINDOW_W as f32, WINDOW_H as f32, seed, wave_set, player_count, sprite_hulls);
+    let result = headless::HeadlessRunner::new(simulation, script, max_ticks).run();
+    let join = |values: Vec<String>| values.join("/");
+    println!("seed: {} ticks: {} score: {} lives: {} wave: {} game over: {}",
+        result.seed, result.ticks,
+        join(result.scores.iter().map(|score| score.to_string()).collect()),
+        join(result.lives.iter().map(|lives| lives.to_string()).collect()),
+        result.wave_level, result.game_over);
+}
+
 fn main() {
-    if let Some(cb) = get_context_builder() {
+    let args: Vec<String> = env::args().collect();
+    let wave_set = match load_wave_set() {
+        Ok(wave_set) => wave_set,
+        Err(e) => {
+            eprintln!("Invalid wave definitions: {}. Game exiting..", e);
+            return;
+        }
+    };
+    let sprite_hulls = Rc::new(load_sprite_hulls());
+    if args.iter().any(|arg| arg == "--benchmark-collisions") {
+        benchmark::run_collision_benchmark(WINDOW_W as f32, WINDOW_H as f32, sprite_hulls);
+    } else if args.iter().any(|arg| arg == "--headless") {
+        run_headless(&args, wave_set, sprite_hulls);
+    } else if let Some(cb) = get_context_builder() {
         let ctx = &mut cb.build().unwrap();
 
-        let game_state = &mut game_event_handler::GameEventHandler::new(ctx, WINDOW_W, WINDOW_H).unwrap();
+        let game_state = &mut game_event_handler::GameEventHandler::new(ctx, WINDOW_W, WINDOW_H, wave_set, sprite_hulls, get_replay_mode(&args)).unwrap();
 
         event::run(ctx, game_state).unwrap();
     } else {
@@ -0,0 +1,42 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+pub struct Menu<T: Copy> {
+    items: Vec<(T, &'static str)>,
+    selected: usize
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new(items: Vec<(T, &'static str)>) -> Menu<T> {
+        Menu {
+            items,
+            selected: 0
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<T> {
+        self.items.get(self.selected).map(|item| item.0)
+    }
+
+    pub fn get_selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn get_labels(&self) -> Vec<&'static str> {
+        self.items.iter().map(|item| item.1).collect()
+    }
+}
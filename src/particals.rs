@@ -4,11 +4,8 @@ Please see the file LICENSE in this distribution
 for license terms. */
 
 use std::f32;
-use ggez::*;
 use super::entity::*;
 use super::body::*;
-use super::asset_manager::*;
-use super::camera::*;
 use super::unit::*;
 
 pub struct Partical {
@@ -28,6 +25,10 @@ impl Partical {
     pub fn new_drone_death(x:f32, y: f32) -> Partical {
         Partical::new(x, y, 1.5, 1.5, f32::consts::PI/2.0, "explosion1".to_string(), 8, 8)
     }
+
+    pub fn new_bomb_flash(x: f32, y: f32) -> Partical {
+        Partical::new(x, y, 6.5, 6.5, f32::consts::PI/2.0, "bomb_flash".to_string(), 8, 8)
+    }
 }
 
 impl Entity for Partical {
@@ -35,8 +36,8 @@ impl Entity for Partical {
         self.unit.update();
     }
 
-    fn draw(&self, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        self.unit.draw(asset_manager, ctx, interpolation_value, camera);
+    fn get_unit(&self) -> &Unit {
+        &self.unit
     }
 
     fn get_body(&self) -> Body {
@@ -51,6 +52,10 @@ impl Entity for Partical {
         self.unit.is_dead
     }
 
+    fn take_damage(&mut self, damage: u32) -> bool {
+        self.unit.take_damage(damage)
+    }
+
     fn set_dead(&mut self) {
         self.unit.set_dead()
     }
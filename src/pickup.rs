@@ -0,0 +1,98 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::f32;
+use nalgebra::Vector2;
+use rand::{Rng, XorShiftRng};
+use super::entity::*;
+use super::body::*;
+use super::collision_shape::*;
+use super::unit::*;
+
+const PICKUP_DRIFT_SPEED: f32 = 2.0;
+const PICKUP_DROP_CHANCE: f32 = 0.12;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PickupType {
+    WeaponUpgrade,
+    Shield,
+    ExtraLife,
+    Bomb,
+    SpeedBoost
+}
+
+pub struct Pickup {
+    unit: Unit,
+    pickup_type: PickupType
+}
+
+impl Pickup {
+    pub fn new(x: f32, y: f32, pickup_type: PickupType) -> Pickup {
+        let asset_key = match pickup_type {
+            PickupType::WeaponUpgrade => "pickup_weapon",
+            PickupType::Shield => "pickup_shield",
+            PickupType::ExtraLife => "pickup_life",
+            PickupType::Bomb => "pickup_bomb",
+            PickupType::SpeedBoost => "pickup_speed"
+        };
+        let mut body = Body::new(x, y, 64.0, 64.0, 0.6, 0.6, f32::consts::PI/2.0, true);
+        body.set_shape(CollisionShape::Circle(32.0));
+        body.velocity = Vector2::new(PICKUP_DRIFT_SPEED, f32::consts::PI);
+        Pickup {
+            unit: Unit::new(body, asset_key.to_string(), 1, 1, true),
+            pickup_type
+        }
+    }
+
+    pub fn create_random_drop(x: f32, y: f32, rng: &mut XorShiftRng) -> Option<Pickup> {
+        let roll: f32 = rng.gen();
+        if roll >= PICKUP_DROP_CHANCE {
+            return None;
+        }
+        //Weighted so the stronger pickups stay rare
+        let pickup_type = match rng.gen_range(0, 20) {
+            0..=6 => PickupType::WeaponUpgrade,
+            7..=10 => PickupType::Shield,
+            11..=14 => PickupType::SpeedBoost,
+            15..=17 => PickupType::Bomb,
+            _ => PickupType::ExtraLife
+        };
+        Some(Pickup::new(x, y, pickup_type))
+    }
+
+    pub fn get_pickup_type(&self) -> PickupType {
+        self.pickup_type
+    }
+}
+
+impl Entity for Pickup {
+    fn update(&mut self) {
+        self.unit.update();
+    }
+
+    fn get_unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    fn get_body(&self) -> Body {
+        self.unit.get_body()
+    }
+
+    fn set_body(&mut self, body: Body) {
+        self.unit.set_body(body)
+    }
+
+    fn is_dead(&self) -> bool {
+        self.unit.is_dead
+    }
+
+    fn take_damage(&mut self, damage: u32) -> bool {
+        self.unit.take_damage(damage)
+    }
+
+    fn set_dead(&mut self) {
+        self.unit.set_dead()
+    }
+}
@@ -9,8 +9,24 @@ use ncollide::shape::Cuboid2;
 use ncollide::bounding_volume;
 
 const ENTITY_AREA_BUFFER_SZ: f32 = 100.0;
+const DEFAULT_PLAYER_MARGIN: f32 = 40.0;
+
+pub struct PlayerBounds {
+    pub margin: f32, //distance kept between a ship's center and the edge of the player area
+    pub push_back: Option<f32> //fraction of an overshoot undone each tick, None stops ships dead at the margin
+}
+
+impl Default for PlayerBounds {
+    fn default() -> PlayerBounds {
+        PlayerBounds {
+            margin: DEFAULT_PLAYER_MARGIN,
+            push_back: None
+        }
+    }
+}
 
 pub struct PlaySpace {
+    pub player_bounds: PlayerBounds,
     pub player_area: Rect,
     pub entity_area: Rect,
     pub player_area_aabb: bounding_volume::AABB<Point<f32, U2>>,
@@ -28,32 +44,68 @@ impl PlaySpace {
         };
         let entity_area = Rect {
             x: player_area.x - ENTITY_AREA_BUFFER_SZ,
-            y: player_area.y,
+            y: player_area.y + ENTITY_AREA_BUFFER_SZ,
             w: player_area.w + (ENTITY_AREA_BUFFER_SZ*2.0),
-            h: player_area.h
+            h: player_area.h + (ENTITY_AREA_BUFFER_SZ*2.0)
         };
         PlaySpace {
+            player_bounds: PlayerBounds::default(),
             player_area,
             entity_area,
             player_area_aabb: PlaySpace::create_aabb_from_rect(&player_area),
             entity_area_aabb: PlaySpace::create_aabb_from_rect(&entity_area),
+            //Reaches well above and below the screen so enemies leaving near an edge still count
             life_loss_area_aabb: PlaySpace::create_aabb_from_rect(&Rect {
                 x: -window_w,
-                y: window_h,
+                y: window_h * 2.0,
                 w: window_w,
-                h: window_h
+                h: window_h * 3.0
             })
         }
     }
 
+    //Rects are anchored at their top left corner with y up, so they span down from y
     fn create_aabb_from_rect(rect: &Rect) -> bounding_volume::AABB<Point<f32, U2>> {
-        bounding_volume::aabb(&Cuboid2::new(Vector2::new(rect.w, rect.h)),&Isometry2::new(Vector2::new(rect.x, rect.y), 0.0))
+        bounding_volume::aabb(
+            &Cuboid2::new(Vector2::new(rect.w / 2.0, rect.h / 2.0)),
+            &Isometry2::new(Vector2::new(rect.x + (rect.w / 2.0), rect.y - (rect.h / 2.0)), 0.0)
+        )
+    }
+
+    fn confine_axis(&self, pos: f32, min: f32, max: f32) -> f32 {
+        let margin = self.player_bounds.margin.min((max - min) / 2.0);
+        let (inner_min, inner_max) = (min + margin, max - margin);
+        let overshoot = if pos < inner_min {
+            pos - inner_min
+        } else if pos > inner_max {
+            pos - inner_max
+        } else {
+            0.0
+        };
+        match self.player_bounds.push_back {
+            Some(push_back) => (pos - overshoot * push_back).max(min).min(max),
+            None => pos - overshoot
+        }
+    }
+
+    //Where a ship at pos ends up once the player bounds have been applied
+    pub fn confine_player(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        let mins = self.player_area_aabb.mins();
+        let maxs = self.player_area_aabb.maxs();
+        Vector2::new(
+            self.confine_axis(pos.x, mins.x, maxs.x),
+            self.confine_axis(pos.y, mins.y, maxs.y)
+        )
     }
 }
 
 impl Clone for PlaySpace {
     fn clone(&self) -> Self {
         PlaySpace {
+            player_bounds: PlayerBounds {
+                margin: self.player_bounds.margin,
+                push_back: self.player_bounds.push_back
+            },
             player_area: self.player_area.clone(),
             entity_area: self.entity_area.clone(),
             player_area_aabb: self.player_area_aabb.clone(),
@@ -63,3 +115,47 @@ impl Clone for PlaySpace {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_aabb(aabb: &bounding_volume::AABB<Point<f32, U2>>, mins: (f32, f32), maxs: (f32, f32)) {
+        assert_eq!((aabb.mins().x, aabb.mins().y), mins);
+        assert_eq!((aabb.maxs().x, aabb.maxs().y), maxs);
+    }
+
+    #[test]
+    fn areas_cover_the_window() {
+        let play_space = PlaySpace::new(640.0, 480.0);
+        assert_aabb(&play_space.player_area_aabb, (0.0, 0.0), (640.0, 480.0));
+        assert_aabb(&play_space.entity_area_aabb, (-100.0, -100.0), (740.0, 580.0));
+        assert_aabb(&play_space.life_loss_area_aabb, (-640.0, -480.0), (0.0, 960.0));
+    }
+
+    #[test]
+    fn ships_stop_at_the_margin() {
+        let play_space = PlaySpace::new(640.0, 480.0);
+        assert_eq!(play_space.player_bounds.push_back, None);
+        assert_eq!(play_space.confine_player(Vector2::new(-10.0, 500.0)), Vector2::new(40.0, 440.0));
+        assert_eq!(play_space.confine_player(Vector2::new(700.0, -5.0)), Vector2::new(600.0, 40.0));
+        assert_eq!(play_space.confine_player(Vector2::new(40.0, 440.0)), Vector2::new(40.0, 440.0));
+        assert_eq!(play_space.confine_player(Vector2::new(320.0, 240.0)), Vector2::new(320.0, 240.0));
+    }
+
+    #[test]
+    fn ships_past_the_margin_are_eased_back() {
+        let mut play_space = PlaySpace::new(640.0, 480.0);
+        play_space.player_bounds.push_back = Some(0.5);
+        //Half of the 30 pixel overshoot is undone on the first tick
+        let mut pos = play_space.confine_player(Vector2::new(10.0, 240.0));
+        assert_eq!(pos, Vector2::new(25.0, 240.0));
+        for _ in 0..10 {
+            let next = play_space.confine_player(pos);
+            assert!(next.x > pos.x && next.x <= 40.0);
+            pos = next;
+        }
+        assert!(40.0 - pos.x < 0.05);
+        //Ships never end up outside the player area itself
+        assert_eq!(play_space.confine_player(Vector2::new(-50.0, 240.0)), Vector2::new(0.0, 240.0));
+    }
+}
@@ -3,35 +3,68 @@ This work is available under the "MIT License”.
 Please see the file LICENSE in this distribution
 for license terms. */
 
-use std::time::{Instant};
 use std::f32;
-use ggez::*;
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Point2};
 use super::entity::*;
 use super::body::*;
-use super::asset_manager::*;
-use super::camera::*;
+use super::collision_shape::*;
 use super::unit::*;
+use super::projectile::*;
+use super::player_weapon::*;
+use super::input::Direction;
+use super::simulation::ms_to_ticks;
+
+const PLAYER_HIT_POINTS: u32 = 3;
+const STARTING_BOMBS: u32 = 3;
+const PLAYER_MOVEMENT_SPEED: f32 = 8.0;
+const MAX_SHIELD: u32 = 2;
+const SHIELD_RECHARGE_TIME: u64 = 6000;
+const RESPAWN_INVULNERABILITY_TIME: u64 = 2000;
+const SPEED_BOOST_TIME: u64 = 10000;
+const SPEED_BOOST_FACTOR: f32 = 1.5;
 
 
 pub struct Player {
-    pub last_death: Instant,
+    pub last_death: u64,
+    pub bombs: u32,
     movement_speed: f32,
-    move_dir: [bool; 4], //up, down, left, right
+    move_dir: [bool; 4], //indexed by Direction
+    analog_dir: Vector2<f32>,
+    weapon: Weapon,
+    shield: u32,
+    shield_recharge_ticks: u64,
+    invulnerable_ticks: u64,
+    speed_boost_ticks: u64,
     unit: Unit
 }
 
 impl Player {
     pub fn new(x: f32, y: f32) -> Player {
-        Player {
-            last_death: Instant::now(),
-            movement_speed: 8.0,
+        let mut player = Player {
+            last_death: 0,
+            bombs: STARTING_BOMBS,
+            movement_speed: PLAYER_MOVEMENT_SPEED,
             move_dir: [false; 4],
+            analog_dir: Vector2::new(0.0, 0.0),
+            weapon: Weapon::new(WeaponType::Single),
+            shield: MAX_SHIELD,
+            shield_recharge_ticks: 0,
+            invulnerable_ticks: 0,
+            speed_boost_ticks: 0,
             unit: Unit::new(
                 Body::new(x, y, 136.0, 96.0, 0.75, 0.75, f32::consts::PI/2.0, true),
                 "player".to_string(), 1, 1, true
             )
-        }
+        };
+        player.unit.body.set_shape(CollisionShape::SpriteHull("playerFighter.png", Box::new(CollisionShape::ConvexPolygon(vec![
+            Point2::new(0.0, 66.0),
+            Point2::new(64.0, -30.0),
+            Point2::new(24.0, -66.0),
+            Point2::new(-24.0, -66.0),
+            Point2::new(-64.0, -30.0)
+        ]))));
+        player.unit.set_max_hit_points(PLAYER_HIT_POINTS);
+        player
     }
 
     fn get_direction_vector(&self) -> [f32; 2] {
@@ -52,44 +85,128 @@ impl Player {
     }
 
     fn get_movement_velocity(&self) -> Vector2<f32> {
-        let dir_vec = self.get_direction_vector();
-        let move_speed = if dir_vec[0] != 0.0 || dir_vec[1] != 0.0 {
-            self.movement_speed
+        //The stick takes over from the digital directions whenever it is off center
+        let (dir_x, dir_y, throttle) = if self.analog_dir.x != 0.0 || self.analog_dir.y != 0.0 {
+            let magnitude = (self.analog_dir.x.powi(2) + self.analog_dir.y.powi(2)).sqrt();
+            (self.analog_dir.x, self.analog_dir.y, magnitude.min(1.0))
         } else {
-            0.0
+            let dir_vec = self.get_direction_vector();
+            let throttle = if dir_vec[0] != 0.0 || dir_vec[1] != 0.0 { 1.0 } else { 0.0 };
+            (dir_vec[1], dir_vec[0], throttle)
         };
-        let direction_angle = dir_vec[0].atan2(dir_vec[1]);
+        let mut move_speed = self.movement_speed * throttle;
+        if self.speed_boost_ticks > 0 {
+            move_speed *= SPEED_BOOST_FACTOR;
+        }
+        let direction_angle = dir_y.atan2(dir_x);
         Vector2::new(move_speed, direction_angle)
     }
 
-    pub fn move_dir(&mut self, dir: u16) {
-        if dir > 4 {
-            return;
-        }
+    pub fn move_dir(&mut self, dir: Direction) {
         self.move_dir[dir as usize] = true;
     }
 
-    pub fn move_dir_cancel(&mut self, dir: u16) {
-        if dir > 4 {
-            return;
-        }
+    pub fn move_dir_cancel(&mut self, dir: Direction) {
         self.move_dir[dir as usize] = false;
     }
 
+    //Kept through death since it mirrors where the stick physically is
+    pub fn set_analog_dir(&mut self, x: f32, y: f32) {
+        self.analog_dir = Vector2::new(x, y);
+    }
+
     pub fn set_alive(&mut self) {
         self.unit.is_dead = false;
+        self.unit.restore_hit_points();
+        self.restore_shield();
+        self.invulnerable_ticks = ms_to_ticks(RESPAWN_INVULNERABILITY_TIME);
+    }
+
+    pub fn get_weapon_type(&self) -> WeaponType {
+        self.weapon.get_weapon_type()
+    }
+
+    pub fn upgrade_weapon(&mut self) {
+        self.weapon.upgrade();
+    }
+
+    pub fn pull_trigger(&mut self) {
+        self.weapon.pull_trigger();
+    }
+
+    pub fn release_trigger(&mut self) {
+        self.weapon.release_trigger();
+    }
+
+    pub fn is_beam_firing(&self) -> bool {
+        !self.unit.is_dead && self.weapon.is_beam_firing()
+    }
+
+    pub fn update_weapon(&mut self) -> Vec<Projectile> {
+        if self.unit.is_dead {
+            return vec![];
+        }
+        self.weapon.update(self.unit.body.pos)
+    }
+
+    pub fn restore_shield(&mut self) {
+        self.shield = MAX_SHIELD;
+        self.shield_recharge_ticks = 0;
+    }
+
+    pub fn get_shield(&self) -> u32 {
+        self.shield
+    }
+
+    pub fn get_max_shield(&self) -> u32 {
+        MAX_SHIELD
+    }
+
+    pub fn is_shielded(&self) -> bool {
+        self.shield > 0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_ticks > 0
+    }
+
+    pub fn is_vulnerable(&self) -> bool {
+        !self.unit.is_dead && !self.is_invulnerable()
+    }
+
+    fn update_shield(&mut self) {
+        if self.shield >= MAX_SHIELD {
+            return;
+        }
+        if self.shield_recharge_ticks > 0 {
+            self.shield_recharge_ticks -= 1;
+        } else {
+            self.shield += 1;
+            self.shield_recharge_ticks = ms_to_ticks(SHIELD_RECHARGE_TIME);
+        }
+    }
+
+    pub fn add_speed_boost(&mut self) {
+        self.speed_boost_ticks = ms_to_ticks(SPEED_BOOST_TIME);
+    }
+
+    fn update_effects(&mut self) {
+        self.update_shield();
+        self.invulnerable_ticks = self.invulnerable_ticks.saturating_sub(1);
+        self.speed_boost_ticks = self.speed_boost_ticks.saturating_sub(1);
     }
 }
 
 impl Entity for Player {
     fn update(&mut self) {
+        self.update_effects();
         let movement_velocity = self.get_movement_velocity();
         self.unit.set_velocity(movement_velocity);
         self.unit.update();
     }
 
-    fn draw(&self, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        self.unit.draw(asset_manager, ctx, interpolation_value, camera);
+    fn get_unit(&self) -> &Unit {
+        &self.unit
     }
 
     fn get_body(&self) -> Body {
@@ -104,9 +221,29 @@ impl Entity for Player {
         self.unit.is_dead
     }
 
+    fn take_damage(&mut self, damage: u32) -> bool {
+        if self.is_invulnerable() {
+            return false;
+        }
+        //Any hit restarts the recharge, absorbed or not
+        self.shield_recharge_ticks = ms_to_ticks(SHIELD_RECHARGE_TIME);
+        if self.is_shielded() {
+            self.shield -= 1;
+            return false;
+        }
+        let killed = self.unit.take_damage(damage);
+        if killed {
+            self.set_dead();
+        }
+        killed
+    }
+
     fn set_dead(&mut self) {
         self.move_dir = [false; 4];
-        self.last_death = Instant::now();
+        self.weapon = Weapon::new(WeaponType::Single);
+        self.shield = 0;
+        self.invulnerable_ticks = 0;
+        self.speed_boost_ticks = 0;
         self.unit.set_dead()
     }
 }
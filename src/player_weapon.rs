@@ -0,0 +1,126 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::f32;
+use nalgebra::Vector2;
+use super::projectile::*;
+use super::simulation::ms_to_ticks;
+
+const DOUBLE_SHOT_SPACING: f32 = 14.0;
+const SPREAD_ARC: f32 = f32::consts::PI / 6.0;
+const SPREAD_COUNT: u32 = 5;
+pub const BEAM_HALF_WIDTH: f32 = 6.0;
+pub const BEAM_DAMAGE: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WeaponType {
+    Single,
+    Double,
+    Spread,
+    Laser,
+    Homing
+}
+
+impl WeaponType {
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            WeaponType::Single => "Single",
+            WeaponType::Double => "Double",
+            WeaponType::Spread => "Spread",
+            WeaponType::Laser => "Laser",
+            WeaponType::Homing => "Homing"
+        }
+    }
+
+    fn get_fire_delay_ms(&self) -> u64 {
+        match *self {
+            WeaponType::Single => 250,
+            WeaponType::Double => 220,
+            WeaponType::Spread => 300,
+            WeaponType::Laser => 0, //the beam is on for as long as the trigger is held
+            WeaponType::Homing => 350
+        }
+    }
+
+    fn next_tier(&self) -> WeaponType {
+        match *self {
+            WeaponType::Single => WeaponType::Double,
+            WeaponType::Double => WeaponType::Spread,
+            WeaponType::Spread => WeaponType::Laser,
+            WeaponType::Laser | WeaponType::Homing => WeaponType::Homing
+        }
+    }
+}
+
+pub struct Weapon {
+    weapon_type: WeaponType,
+    fire_delay: u64,
+    cooldown: u64,
+    trigger_held: bool
+}
+
+impl Weapon {
+    pub fn new(weapon_type: WeaponType) -> Weapon {
+        Weapon {
+            weapon_type,
+            fire_delay: ms_to_ticks(weapon_type.get_fire_delay_ms()),
+            cooldown: 0,
+            trigger_held: false
+        }
+    }
+
+    pub fn get_weapon_type(&self) -> WeaponType {
+        self.weapon_type
+    }
+
+    pub fn upgrade(&mut self) {
+        let trigger_held = self.trigger_held;
+        *self = Weapon::new(self.weapon_type.next_tier());
+        self.trigger_held = trigger_held;
+    }
+
+    pub fn pull_trigger(&mut self) {
+        self.trigger_held = true;
+    }
+
+    pub fn release_trigger(&mut self) {
+        self.trigger_held = false;
+    }
+
+    //The laser fires no projectiles, its beam is resolved each tick by the entity manager
+    pub fn is_beam_firing(&self) -> bool {
+        self.weapon_type == WeaponType::Laser && self.trigger_held
+    }
+
+    pub fn update(&mut self, origin: Vector2<f32>) -> Vec<Projectile> {
+        self.cooldown = self.cooldown.saturating_sub(1);
+        if !self.trigger_held || self.cooldown > 0 || self.weapon_type == WeaponType::Laser {
+            return vec![];
+        }
+        self.cooldown = self.fire_delay;
+        self.fire(origin)
+    }
+
+    fn fire(&self, origin: Vector2<f32>) -> Vec<Projectile> {
+        match self.weapon_type {
+            WeaponType::Single => vec![Projectile::new_player(origin.x, origin.y, 0.0)],
+            WeaponType::Double => vec![
+                Projectile::new_player(origin.x, origin.y + DOUBLE_SHOT_SPACING / 2.0, 0.0),
+                Projectile::new_player(origin.x, origin.y - DOUBLE_SHOT_SPACING / 2.0, 0.0)
+            ],
+            WeaponType::Spread => {
+                let angle_step = SPREAD_ARC / (SPREAD_COUNT - 1) as f32;
+                (0..SPREAD_COUNT).map(|i| {
+                    Projectile::new_player(origin.x, origin.y, -(SPREAD_ARC / 2.0) + angle_step * i as f32)
+                }).collect()
+            },
+            WeaponType::Laser => vec![],
+            WeaponType::Homing => vec![
+                Projectile::new_homing(origin.x, origin.y + DOUBLE_SHOT_SPACING, f32::consts::PI / 8.0),
+                Projectile::new_homing(origin.x, origin.y - DOUBLE_SHOT_SPACING, -f32::consts::PI / 8.0)
+            ]
+        }
+    }
+}
@@ -4,34 +4,117 @@ Please see the file LICENSE in this distribution
 for license terms. */
 
 use std::f32;
-use ggez::*;
 use nalgebra::Vector2;
 use super::entity::*;
 use super::body::*;
-use super::asset_manager::*;
-use super::camera::*;
+use super::collision_shape::*;
 use super::unit::*;
+use super::arena::EntityId;
+
+const PLAYER_PROJECTILE_DAMAGE: u32 = 1;
+const PLAYER_PROJECTILE_SPEED: f32 = 15.0;
+const HOMING_SPEED: f32 = 9.0;
+const HOMING_TURN_RATE: f32 = 0.08;
+const HOMING_DAMAGE: u32 = 2;
 
 pub struct Projectile {
     unit: Unit,
-    player_owned: bool
+    player_owned: bool,
+    owner: usize,
+    damage: u32,
+    turn_rate: Option<f32>,
+    target: Option<EntityId>
 }
 
 impl Projectile {
-    pub fn new(x: f32, y: f32, player_owned: bool) -> Projectile {
-        let rotation = if player_owned {f32::consts::PI/2.0} else {(f32::consts::PI*3.0)/2.0};
-        let mut body = Body::new(x, y, 64.0, 32.0, 0.5, 0.5, rotation, true);
-        body.velocity = Vector2::new(15.0, if player_owned {0.0} else {f32::consts::PI});
+    fn create_shape() -> CollisionShape {
+        CollisionShape::Capsule(16.0, 16.0)
+    }
+
+    fn new_player_shot(x: f32, y: f32, angle: f32, speed: f32, scale: (f32, f32), asset_key: &str, damage: u32) -> Projectile {
+        let mut body = Body::new(x, y, 64.0, 32.0, scale.0, scale.1, (f32::consts::PI/2.0) - angle, true);
+        body.set_shape(Projectile::create_shape());
+        body.velocity = Vector2::new(speed, angle);
 
         Projectile {
-            unit: Unit::new(body, "projectile1".to_string(), 1, 1, true),
-            player_owned
+            unit: Unit::new(body, asset_key.to_string(), 1, 1, true),
+            player_owned: true,
+            owner: 0,
+            damage,
+            turn_rate: None,
+            target: None
         }
     }
+
+    pub fn new_player(x: f32, y: f32, angle: f32) -> Projectile {
+        Projectile::new_player_shot(x, y, angle, PLAYER_PROJECTILE_SPEED, (0.5, 0.5), "projectile1", PLAYER_PROJECTILE_DAMAGE)
+    }
+
+    pub fn new_homing(x: f32, y: f32, angle: f32) -> Projectile {
+        let mut projectile = Projectile::new_player_shot(x, y, angle, HOMING_SPEED, (0.6, 0.6), "missile", HOMING_DAMAGE);
+        projectile.turn_rate = Some(HOMING_TURN_RATE);
+        projectile
+    }
     
+    pub fn new_enemy(x: f32, y: f32, angle: f32, speed: f32, damage: u32) -> Projectile {
+        let mut body = Body::new(x, y, 64.0, 32.0, 0.5, 0.5, (f32::consts::PI/2.0) - angle, true);
+        body.set_shape(Projectile::create_shape());
+        body.velocity = Vector2::new(speed, angle);
+
+        Projectile {
+            unit: Unit::new(body, "projectile1".to_string(), 1, 1, true),
+            player_owned: false,
+            owner: 0,
+            damage,
+            turn_rate: None,
+            target: None
+        }
+    }
+
     pub fn is_player_owned(&self) -> bool {
         self.player_owned
     }
+
+    //Index of the player credited with this shot's kills, only meaningful when player owned
+    pub fn get_owner(&self) -> usize {
+        self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: usize) {
+        self.owner = owner;
+    }
+
+    pub fn get_damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn is_homing(&self) -> bool {
+        self.turn_rate.is_some()
+    }
+
+    pub fn get_target(&self) -> Option<EntityId> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Option<EntityId>) {
+        self.target = target;
+    }
+
+    pub fn update_homing(&mut self, target: Option<Vector2<f32>>) {
+        if let (Some(turn_rate), Some(target)) = (self.turn_rate, target) {
+            let body = &mut self.unit.body;
+            let target_angle = (target.y - body.pos.y).atan2(target.x - body.pos.x);
+            let mut angle_diff = target_angle - body.velocity.y;
+            while angle_diff > f32::consts::PI {
+                angle_diff -= 2.0 * f32::consts::PI;
+            }
+            while angle_diff < -f32::consts::PI {
+                angle_diff += 2.0 * f32::consts::PI;
+            }
+            body.velocity.y += angle_diff.max(-turn_rate).min(turn_rate);
+            body.rotation = (f32::consts::PI/2.0) - body.velocity.y;
+        }
+    }
 }
 
 impl Entity for Projectile {
@@ -39,8 +122,8 @@ impl Entity for Projectile {
         self.unit.update();
     }
 
-    fn draw(&self, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        self.unit.draw(asset_manager, ctx, interpolation_value, camera);
+    fn get_unit(&self) -> &Unit {
+        &self.unit
     }
 
     fn get_body(&self) -> Body {
@@ -55,6 +138,10 @@ impl Entity for Projectile {
         self.unit.is_dead
     }
 
+    fn take_damage(&mut self, damage: u32) -> bool {
+        self.unit.take_damage(damage)
+    }
+
     fn set_dead(&mut self) {
         self.unit.set_dead()
     }
@@ -0,0 +1,383 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::f32;
+use ggez::*;
+use nalgebra::Point2;
+use super::asset_manager::*;
+use super::camera::*;
+use super::entity::*;
+use super::unit::*;
+use super::simulation::*;
+use super::game_state::{GameState, PlayerState};
+use super::player_weapon::BEAM_HALF_WIDTH;
+use super::high_scores::*;
+use super::input::ACTIONS;
+use super::key_bindings::*;
+
+const HIT_FLASH_COLOR: graphics::Color = graphics::Color { r: 1.0, g: 0.25, b: 0.25, a: 1.0 };
+const SHIELD_COLOR: graphics::Color = graphics::Color { r: 0.75, g: 0.9, b: 1.0, a: 1.0 };
+const PLAYER_TINTS: [graphics::Color; 2] = [
+    graphics::Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 },
+    graphics::Color { r: 1.0, g: 0.7, b: 0.4, a: 1.0 }
+];
+const POPUP_COLOR: graphics::Color = graphics::Color { r: 1.0, g: 1.0, b: 0.6, a: 1.0 };
+const INVULNERABLE_BLINK_TICKS: u64 = 4;
+const BACKDROP_COLOR: graphics::Color = graphics::Color { r: 0.0, g: 0.0, b: 0.0, a: 0.85 };
+const HIGH_SCORE_TOP: f32 = 90.0;
+const HIGH_SCORE_ROW_H: f32 = 30.0;
+const MENU_ROW_H: f32 = 40.0;
+const MENU_SELECTED_COLOR: graphics::Color = graphics::Color { r: 1.0, g: 0.85, b: 0.3, a: 1.0 };
+const BOSS_HEALTH_BAR_COLOR: graphics::Color = graphics::Color { r: 0.9, g: 0.2, b: 0.3, a: 1.0 };
+const BOSS_HEALTH_BAR_Y: f32 = 40.0;
+const BOSS_HEALTH_BAR_H: f32 = 10.0;
+const LASER_SPRITE_W: f32 = 32.0;
+const LASER_SPRITE_H: f32 = 64.0;
+
+pub struct Renderer {
+    asset_manager: AssetManager,
+    camera: Camera
+}
+
+impl Renderer {
+    pub fn new(ctx: &mut Context, window_w: u32, window_h: u32) -> GameResult<Renderer> {
+        let asset_manager = AssetManager::new(ctx, window_w, window_h)?;
+        Ok(Renderer {
+            asset_manager,
+            camera: Camera::new(window_w, window_h)
+        })
+    }
+
+    fn get_unit_draw_param(&self, unit: &Unit, interpolation_value: f32) -> graphics::DrawParam {
+        let view_position = unit.body.get_view_position(interpolation_value, &self.camera);
+        let frame = unit.get_animation_frame();
+        graphics::DrawParam {
+            src: graphics::Rect {
+                x: frame[0],
+                y: frame[1],
+                w: frame[2],
+                h: frame[3]
+            },
+            dest: Point2::new(view_position.x, view_position.y),
+            rotation: unit.body.rotation,
+            scale: Point2::new(unit.body.scale.x, unit.body.scale.y),
+            offset: Point2::new(0.5, 0.5),
+            color: if unit.is_hit_flashing() { Some(HIT_FLASH_COLOR) } else { None },
+            .. Default::default()
+        }
+    }
+
+    fn draw_entity(&self, ctx: &mut Context, entity: &Entity, interpolation_value: f32) {
+        let unit = entity.get_unit();
+        if !unit.is_dead {
+            self.asset_manager.draw_asset(unit.asset_key.clone(), ctx, self.get_unit_draw_param(unit, interpolation_value));
+        }
+    }
+
+    fn multiply_colors(a: graphics::Color, b: graphics::Color) -> graphics::Color {
+        graphics::Color::new(a.r * b.r, a.g * b.g, a.b * b.b, a.a * b.a)
+    }
+
+    fn draw_player(&self, ctx: &mut Context, game_state: &GameState, player: usize, interpolation_value: f32) {
+        let drawn = &game_state.players[player].player;
+        let unit = drawn.get_unit();
+        let blink_hidden = drawn.is_invulnerable() && (game_state.tick / INVULNERABLE_BLINK_TICKS) % 2 == 1;
+        if let Some(beam_length) = game_state.players[player].beam_length {
+            //The laser sprite is stretched along the beam, starting from where the ship is drawn
+            let start = unit.body.get_view_position(interpolation_value, &self.camera);
+            self.asset_manager.draw_asset("laser".to_string(), ctx, graphics::DrawParam {
+                dest: Point2::new(start.x + beam_length / 2.0, start.y),
+                rotation: f32::consts::PI / 2.0,
+                scale: Point2::new(BEAM_HALF_WIDTH * 2.0 / LASER_SPRITE_W, beam_length / LASER_SPRITE_H),
+                offset: Point2::new(0.5, 0.5),
+                .. Default::default()
+            });
+        }
+        if !unit.is_dead && !blink_hidden {
+            let mut draw_param = self.get_unit_draw_param(unit, interpolation_value);
+            if draw_param.color.is_none() {
+                let tint = PLAYER_TINTS[player % PLAYER_TINTS.len()];
+                draw_param.color = Some(if drawn.is_shielded() { Renderer::multiply_colors(tint, SHIELD_COLOR) } else { tint });
+            }
+            self.asset_manager.draw_asset(unit.asset_key.clone(), ctx, draw_param);
+        }
+    }
+
+    pub fn draw_world(&self, ctx: &mut Context, game_state: &GameState, interpolation_value: f32) {
+        for projectile in game_state.projectiles.values(){
+            self.draw_entity(ctx, projectile, interpolation_value);
+        }
+        for enemy in &game_state.enemies {
+            self.draw_entity(ctx, enemy, interpolation_value);
+        }
+        if let Some(ref boss) = game_state.boss {
+            for part in boss.get_parts() {
+                self.draw_entity(ctx, part, interpolation_value);
+            }
+        }
+        for pickup in &game_state.pickups {
+            self.draw_entity(ctx, pickup, interpolation_value);
+        }
+        for player in 0..game_state.players.len() {
+            self.draw_player(ctx, game_state, player, interpolation_value);
+        }
+
+        for partical in &game_state.particals {
+            self.draw_entity(ctx, partical, interpolation_value);
+        }
+        self.draw_score_popups(ctx, game_state);
+    }
+
+    fn draw_score_popups(&self, ctx: &mut Context, game_state: &GameState) {
+        for (player, state) in game_state.players.iter().enumerate() {
+            let color = Renderer::multiply_colors(POPUP_COLOR, PLAYER_TINTS[player % PLAYER_TINTS.len()]);
+            for popup in state.score.get_popups() {
+                let popup_text = graphics::Text::new(ctx,
+                    format!("{}", popup.value).as_str(),
+                    &self.asset_manager.small_font
+                ).unwrap();
+                let view_position = self.camera.get_view_position(&popup.pos);
+                graphics::set_color(ctx, graphics::Color::new(color.r, color.g, color.b, popup.get_alpha())).unwrap();
+                self.asset_manager.draw_text_centered_at(ctx, popup_text, Point2::new(view_position.x, view_position.y));
+            }
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+
+    pub fn draw_hud(&self, ctx: &mut Context, simulation: &Simulation) {
+        if simulation.get_player_count() > 1 {
+            self.draw_coop_hud(ctx, simulation);
+        } else {
+            let state = &simulation.game_state.players[0];
+            self.draw_lives(ctx, state);
+            self.draw_hull(ctx, state);
+            self.draw_score(ctx, state);
+        }
+        self.draw_level(ctx, simulation);
+        self.draw_boss_health(ctx, simulation);
+    }
+
+    fn draw_lives(&self, ctx: &mut Context, state: &PlayerState) {
+        let next_level_text = graphics::Text::new(ctx,
+            format!("Lives: {}  Bombs: {}", state.lives, state.player.bombs).as_str(),
+            &self.asset_manager.med_splash_font
+        ).unwrap();
+        self.asset_manager.draw_top_left_text(
+            ctx, next_level_text
+        );
+    }
+
+    fn get_hull_string(state: &PlayerState) -> String {
+        let player_unit = state.player.get_unit();
+        format!("Hull: {}/{}  Shield: {}/{}  Weapon: {}", player_unit.hit_points, player_unit.max_hit_points,
+            state.player.get_shield(), state.player.get_max_shield(),
+            state.player.get_weapon_type().get_name())
+    }
+
+    fn draw_hull(&self, ctx: &mut Context, state: &PlayerState) {
+        let hull_text = graphics::Text::new(ctx,
+            Renderer::get_hull_string(state).as_str(),
+            &self.asset_manager.med_splash_font
+        ).unwrap();
+        self.asset_manager.draw_bottom_left_text(
+            ctx, hull_text
+        );
+    }
+
+    fn draw_level(&self, ctx: &mut Context, simulation: &Simulation) {
+        let next_level_text = graphics::Text::new(ctx,
+            format!("Level: {}", simulation.wave_manager.get_wave_level()).as_str(),
+            &self.asset_manager.med_splash_font
+        ).unwrap();
+        self.asset_manager.draw_top_centered_text(
+            ctx, next_level_text
+        );
+    }
+
+    fn draw_score(&self, ctx: &mut Context, state: &PlayerState) {
+        let next_level_text = graphics::Text::new(ctx,
+            format!("Score: {:06} x{}", state.score.get_total(), state.score.get_multiplier()).as_str(),
+            &self.asset_manager.med_splash_font
+        ).unwrap();
+        self.asset_manager.draw_top_right_text(
+            ctx, next_level_text
+        );
+    }
+
+    //Player one reads down the left edge and player two down the right, in the small font so both fit
+    fn draw_coop_hud(&self, ctx: &mut Context, simulation: &Simulation) {
+        for (player, state) in simulation.game_state.players.iter().enumerate() {
+            let status = if state.is_out() {
+                format!("P{} {:06}  OUT", player + 1, state.score.get_total())
+            } else {
+                format!("P{} {:06} x{}  Lives: {}  Bombs: {}", player + 1, state.score.get_total(),
+                    state.score.get_multiplier(), state.lives, state.player.bombs)
+            };
+            let status_text = graphics::Text::new(ctx, status.as_str(), &self.asset_manager.small_font).unwrap();
+            let hull_text = graphics::Text::new(ctx, Renderer::get_hull_string(state).as_str(), &self.asset_manager.small_font).unwrap();
+            graphics::set_color(ctx, PLAYER_TINTS[player % PLAYER_TINTS.len()]).unwrap();
+            if player == 0 {
+                self.asset_manager.draw_top_left_text(ctx, status_text);
+                self.asset_manager.draw_bottom_left_text(ctx, hull_text);
+            } else {
+                self.asset_manager.draw_top_right_text(ctx, status_text);
+                self.asset_manager.draw_bottom_right_text(ctx, hull_text);
+            }
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+
+    fn draw_boss_health(&self, ctx: &mut Context, simulation: &Simulation) {
+        if let Some(ref boss) = simulation.game_state.boss {
+            let bar_w = self.camera.size.x as f32 * 0.6;
+            let bar_x = (self.camera.size.x as f32 - bar_w) / 2.0;
+            graphics::set_color(ctx, BOSS_HEALTH_BAR_COLOR).unwrap();
+            graphics::rectangle(ctx, graphics::DrawMode::Fill, graphics::Rect {
+                x: bar_x,
+                y: BOSS_HEALTH_BAR_Y,
+                w: bar_w * boss.get_health_fraction(),
+                h: BOSS_HEALTH_BAR_H
+            }).unwrap();
+            graphics::set_color(ctx, graphics::WHITE).unwrap();
+            graphics::rectangle(ctx, graphics::DrawMode::Line(1.0), graphics::Rect {
+                x: bar_x,
+                y: BOSS_HEALTH_BAR_Y,
+                w: bar_w,
+                h: BOSS_HEALTH_BAR_H
+            }).unwrap();
+        }
+    }
+
+    pub fn draw_wave_clear(&self, ctx: &mut Context, simulation: &Simulation) {
+        let next_level_text = graphics::Text::new(ctx,
+            format!("Press SPACE to start level {}!",
+            simulation.wave_manager.get_wave_level() + 1).as_str(),
+            &self.asset_manager.med_splash_font
+        ).unwrap();
+        self.asset_manager.draw_bottom_centered_text(
+            ctx, next_level_text
+        );
+    }
+
+    pub fn draw_title(&self, ctx: &mut Context) {
+        let title_text = graphics::Text::new(ctx, "Arcade Shooter", &self.asset_manager.large_splash_font).unwrap();
+        let start_text = graphics::Text::new(ctx, "SPACE/A start, 2/X co-op, H/Y scores, O/BACK options", &self.asset_manager.med_splash_font).unwrap();
+        self.asset_manager.draw_centered_text(
+            ctx, title_text
+        );
+        self.asset_manager.draw_bottom_centered_text(
+            ctx, start_text
+        );
+    }
+
+    pub fn draw_game_over(&self, ctx: &mut Context) {
+        let game_over_text = graphics::Text::new(ctx, "Game Over", &self.asset_manager.large_splash_font).unwrap();
+        let restart_text = graphics::Text::new(ctx, "SPACE/A to play again, ESC/B for title", &self.asset_manager.med_splash_font).unwrap();
+        self.asset_manager.draw_centered_text(
+            ctx, game_over_text
+        );
+        self.asset_manager.draw_bottom_centered_text(
+            ctx, restart_text
+        );
+    }
+
+    pub fn draw_pause_menu(&self, ctx: &mut Context, labels: &[&str], selected: usize) {
+        self.draw_backdrop(ctx);
+        let paused_text = graphics::Text::new(ctx, "Paused", &self.asset_manager.large_splash_font).unwrap();
+        self.asset_manager.draw_top_centered_text(ctx, paused_text);
+
+        let center_x = self.camera.size.x as f32 / 2.0;
+        let menu_top = (self.camera.size.y as f32 - labels.len() as f32 * MENU_ROW_H) / 2.0;
+        for (i, label) in labels.iter().enumerate() {
+            let label_text = if i == selected {
+                graphics::set_color(ctx, MENU_SELECTED_COLOR).unwrap();
+                graphics::Text::new(ctx, format!("> {} <", label).as_str(), &self.asset_manager.med_splash_font).unwrap()
+            } else {
+                graphics::Text::new(ctx, label, &self.asset_manager.med_splash_font).unwrap()
+            };
+            self.asset_manager.draw_text_centered_at(ctx, label_text, Point2::new(center_x, menu_top + i as f32 * MENU_ROW_H));
+            graphics::set_color(ctx, graphics::WHITE).unwrap();
+        }
+    }
+
+    pub fn draw_options(&self, ctx: &mut Context, key_bindings: &KeyBindings, player: usize, selected: usize, awaiting_key: bool) {
+        self.draw_backdrop(ctx);
+        let title = format!("< Player {} Controls >", player + 1);
+        let title_text = graphics::Text::new(ctx, title.as_str(), &self.asset_manager.large_splash_font).unwrap();
+        self.asset_manager.draw_top_centered_text(ctx, title_text);
+
+        let center_x = self.camera.size.x as f32 / 2.0;
+        for (i, action) in ACTIONS.iter().enumerate() {
+            let keys = if i == selected && awaiting_key {
+                "press a key...".to_string()
+            } else {
+                key_bindings.get_keys(player, *action).iter().map(|keycode| keycode.name()).collect::<Vec<String>>().join(", ")
+            };
+            if i == selected {
+                graphics::set_color(ctx, MENU_SELECTED_COLOR).unwrap();
+            }
+            let row = format!("{}  {}", action.get_label(), keys);
+            let row_text = graphics::Text::new(ctx, row.as_str(), &self.asset_manager.med_splash_font).unwrap();
+            self.asset_manager.draw_text_centered_at(ctx, row_text, Point2::new(center_x, HIGH_SCORE_TOP + i as f32 * HIGH_SCORE_ROW_H));
+            graphics::set_color(ctx, graphics::WHITE).unwrap();
+        }
+
+        let help = if awaiting_key { "ESC to cancel" } else { "RETURN rebind, R reset defaults, ESC back" };
+        let help_text = graphics::Text::new(ctx, help, &self.asset_manager.med_splash_font).unwrap();
+        self.asset_manager.draw_bottom_centered_text(ctx, help_text);
+    }
+
+    fn draw_backdrop(&self, ctx: &mut Context) {
+        graphics::set_color(ctx, BACKDROP_COLOR).unwrap();
+        graphics::rectangle(ctx, graphics::DrawMode::Fill, graphics::Rect {
+            x: 0.0,
+            y: 0.0,
+            w: self.camera.size.x as f32,
+            h: self.camera.size.y as f32
+        }).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+
+    pub fn draw_name_entry(&self, ctx: &mut Context, name_entry: &NameEntry, player: Option<usize>) {
+        self.draw_backdrop(ctx);
+        let title = match player {
+            Some(player) => format!("Player {} High Score!", player + 1),
+            None => "New High Score!".to_string()
+        };
+        let title_text = graphics::Text::new(ctx, title.as_str(), &self.asset_manager.large_splash_font).unwrap();
+        self.asset_manager.draw_top_centered_text(ctx, title_text);
+
+        let initials: String = name_entry.get_initials().chars().enumerate().map(|(i, letter)| {
+            if i == name_entry.get_cursor() {
+                format!("[{}]", letter)
+            } else {
+                format!(" {} ", letter)
+            }
+        }).collect();
+        let initials_text = graphics::Text::new(ctx, initials.as_str(), &self.asset_manager.large_splash_font).unwrap();
+        self.asset_manager.draw_centered_text(ctx, initials_text);
+
+        let help_text = graphics::Text::new(ctx, "W/S or d-pad letter, A/D move, SPACE/A confirm", &self.asset_manager.med_splash_font).unwrap();
+        self.asset_manager.draw_bottom_centered_text(ctx, help_text);
+    }
+
+    pub fn draw_high_scores(&self, ctx: &mut Context, high_scores: &HighScoreTable) {
+        self.draw_backdrop(ctx);
+        let title_text = graphics::Text::new(ctx, "High Scores", &self.asset_manager.large_splash_font).unwrap();
+        self.asset_manager.draw_top_centered_text(ctx, title_text);
+
+        let center_x = self.camera.size.x as f32 / 2.0;
+        for (i, entry) in high_scores.get_entries().iter().enumerate() {
+            let row_text = graphics::Text::new(ctx,
+                format!("{:>2}. {}  {:06}  Wave {:<3} {}", i + 1, entry.initials, entry.score, entry.wave, entry.date).as_str(),
+                &self.asset_manager.small_font
+            ).unwrap();
+            self.asset_manager.draw_text_centered_at(ctx, row_text, Point2::new(center_x, HIGH_SCORE_TOP + i as f32 * HIGH_SCORE_ROW_H));
+        }
+
+        let back_text = graphics::Text::new(ctx, "Press SPACE to return", &self.asset_manager.med_splash_font).unwrap();
+        self.asset_manager.draw_bottom_centered_text(ctx, back_text);
+    }
+}
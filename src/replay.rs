@@ -0,0 +1,284 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use super::input;
+use super::input::{Action, SimulationInput};
+use super::game_state::MAX_PLAYERS;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayInput {
+    Action(usize, Action, bool, bool), //player, action, pressed, repeat
+    AnalogMove(usize, f32, f32)
+}
+
+impl ReplayInput {
+    pub fn get_simulation_inputs(&self) -> Vec<SimulationInput> {
+        match *self {
+            ReplayInput::Action(player, action, pressed, repeat) => input::from_action(player, action, pressed, repeat),
+            ReplayInput::AnalogMove(player, x, y) => vec![SimulationInput::PlayerAnalogMove(player, x, y)]
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayEvent {
+    pub tick: u64,
+    pub input: ReplayInput
+}
+
+pub struct Replay {
+    pub seed: u32,
+    pub player_count: usize,
+    pub events: Vec<ReplayEvent>
+}
+
+pub enum ReplayMode {
+    Off,
+    Record(ReplayRecorder),
+    Playback(ReplayPlayer)
+}
+
+fn invalid_data(line_number: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("replay line {}: {}", line_number, message))
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        Replay::parse(BufReader::new(File::open(path)?))
+    }
+
+    fn parse<R: BufRead>(reader: R) -> io::Result<Replay> {
+        let mut lines = reader.lines();
+        let seed = match lines.next() {
+            Some(line) => Replay::parse_seed(&line?)?,
+            None => return Err(invalid_data(1, "missing seed"))
+        };
+        let mut player_count = 1;
+        let mut events = vec![];
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            if line.starts_with("players") && events.is_empty() {
+                player_count = Replay::parse_player_count(i + 2, &line)?;
+            } else if !line.trim().is_empty() {
+                events.push(Replay::parse_event(i + 2, &line)?);
+            }
+        }
+        Ok(Replay {
+            seed,
+            player_count,
+            events
+        })
+    }
+
+    fn parse_seed(line: &str) -> io::Result<u32> {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next().and_then(|seed| seed.parse().ok())) {
+            (Some("seed"), Some(seed)) => Ok(seed),
+            _ => Err(invalid_data(1, "expected 'seed <number>'"))
+        }
+    }
+
+    fn parse_player_count(line_number: usize, line: &str) -> io::Result<usize> {
+        match line.split_whitespace().nth(1).and_then(|player_count| player_count.parse().ok()) {
+            Some(player_count) if player_count >= 1 && player_count <= MAX_PLAYERS => Ok(player_count),
+            Some(_) => Err(invalid_data(line_number, &format!("player count must be between 1 and {}", MAX_PLAYERS))),
+            None => Err(invalid_data(line_number, "expected 'players <number>'"))
+        }
+    }
+
+    // <tick> <down|up> <repeat 0|1> <action name> [player] or <tick> analog <x> <y> [player]
+    // Players are numbered from 1 and default to the first
+    fn parse_event(line_number: usize, line: &str) -> io::Result<ReplayEvent> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let tick = parts.get(0).and_then(|tick| tick.parse().ok());
+        let input = match parts.get(1) {
+            Some(&"analog") => Replay::parse_analog_move(&parts[2..]),
+            Some(&"down") => Replay::parse_action(true, &parts[2..]),
+            Some(&"up") => Replay::parse_action(false, &parts[2..]),
+            _ => None
+        };
+        match (tick, input) {
+            (Some(tick), Some(input)) => Ok(ReplayEvent {
+                tick,
+                input
+            }),
+            _ => Err(invalid_data(line_number, "malformed input event"))
+        }
+    }
+
+    fn parse_player(part: Option<&&str>) -> Option<usize> {
+        match part {
+            Some(player) => player.parse::<usize>().ok().and_then(|player| player.checked_sub(1)),
+            None => Some(0)
+        }
+    }
+
+    fn parse_action(pressed: bool, parts: &[&str]) -> Option<ReplayInput> {
+        let repeat = match parts.get(0) {
+            Some(&"1") => true,
+            Some(&"0") => false,
+            _ => return None
+        };
+        let action = parts.get(1).and_then(|name| Action::from_name(name))?;
+        let player = Replay::parse_player(parts.get(2))?;
+        if parts.len() > 3 {
+            return None;
+        }
+        Some(ReplayInput::Action(player, action, pressed, repeat))
+    }
+
+    fn parse_analog_move(parts: &[&str]) -> Option<ReplayInput> {
+        if parts.len() < 2 || parts.len() > 3 {
+            return None;
+        }
+        let player = Replay::parse_player(parts.get(2))?;
+        match (parts[0].parse(), parts[1].parse()) {
+            (Ok(x), Ok(y)) => Some(ReplayInput::AnalogMove(player, x, y)),
+            _ => None
+        }
+    }
+}
+
+pub struct ReplayRecorder {
+    pub seed: u32,
+    file: File
+}
+
+impl ReplayRecorder {
+    pub fn create(path: &Path, seed: u32) -> io::Result<ReplayRecorder> {
+        let mut file = File::create(path)?;
+        writeln!(file, "seed {}", seed)?;
+        Ok(ReplayRecorder {
+            seed,
+            file
+        })
+    }
+
+    // Written once the run starts, before any of its events
+    pub fn record_player_count(&mut self, player_count: usize) -> io::Result<()> {
+        writeln!(self.file, "players {}", player_count)?;
+        self.file.flush()
+    }
+
+    // Events are flushed as they happen so a replay survives the game crashing
+    pub fn record(&mut self, event: ReplayEvent) -> io::Result<()> {
+        match event.input {
+            ReplayInput::Action(player, action, pressed, repeat) => writeln!(self.file, "{} {} {} {} {}",
+                event.tick,
+                if pressed { "down" } else { "up" },
+                if repeat { 1 } else { 0 },
+                action.get_name(),
+                player + 1
+            )?,
+            //f32 Display round-trips exactly, keeping playback deterministic
+            ReplayInput::AnalogMove(player, x, y) => writeln!(self.file, "{} analog {} {} {}", event.tick, x, y, player + 1)?
+        }
+        self.file.flush()
+    }
+}
+
+pub struct ReplayPlayer {
+    pub seed: u32,
+    pub player_count: usize,
+    events: Vec<ReplayEvent>,
+    next_event: usize
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer {
+            seed: replay.seed,
+            player_count: replay.player_count,
+            events: replay.events,
+            next_event: 0
+        }
+    }
+
+    pub fn next_due_event(&mut self, tick: u64) -> Option<ReplayEvent> {
+        if self.next_event < self.events.len() && self.events[self.next_event].tick <= tick {
+            self.next_event += 1;
+            return Some(self.events[self.next_event - 1]);
+        }
+        None
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+
+    fn parse_str(text: &str) -> io::Result<Replay> {
+        Replay::parse(text.as_bytes())
+    }
+
+    fn assert_error_line(text: &str, line_number: usize) {
+        match parse_str(text) {
+            Ok(_) => panic!("expected {:?} to be rejected", text),
+            Err(e) => {
+                assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                assert!(e.to_string().starts_with(&format!("replay line {}:", line_number)), "{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn recorded_events_load_back_unchanged() {
+        let events = vec![
+            ReplayEvent { tick: 0, input: ReplayInput::Action(0, Action::Fire, true, false) },
+            ReplayEvent { tick: 3, input: ReplayInput::Action(1, Action::MoveUp, true, true) },
+            ReplayEvent { tick: 7, input: ReplayInput::AnalogMove(1, -0.3333333, 0.1) },
+            ReplayEvent { tick: 12, input: ReplayInput::Action(0, Action::Fire, false, false) },
+            ReplayEvent { tick: 12, input: ReplayInput::AnalogMove(0, 1.0, -1.0) }
+        ];
+        let path = env::temp_dir().join(format!("replay_round_trip_{}.replay", ::std::process::id()));
+        {
+            let mut recorder = ReplayRecorder::create(&path, 42).unwrap();
+            recorder.record_player_count(2).unwrap();
+            for event in &events {
+                recorder.record(*event).unwrap();
+            }
+        }
+        let replay = Replay::load(&path);
+        fs::remove_file(&path).unwrap();
+        let replay = replay.unwrap();
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.player_count, 2);
+        assert_eq!(replay.events, events);
+    }
+
+    #[test]
+    fn player_defaults_to_the_first() {
+        let replay = parse_str("seed 1\n5 down 0 fire\n6 analog 0.5 0\n").unwrap();
+        assert_eq!(replay.player_count, 1);
+        assert_eq!(replay.events, vec![
+            ReplayEvent { tick: 5, input: ReplayInput::Action(0, Action::Fire, true, false) },
+            ReplayEvent { tick: 6, input: ReplayInput::AnalogMove(0, 0.5, 0.0) }
+        ]);
+    }
+
+    #[test]
+    fn malformed_lines_report_their_line_number() {
+        assert_error_line("", 1);
+        assert_error_line("sed 1\n", 1);
+        assert_error_line("seed 1\nplayers two\n", 2);
+        assert_error_line("seed 1\nplayers 0\n", 2);
+        assert_error_line("seed 1\nplayers 3\n", 2);
+        assert_error_line("seed 1\n\n4 down 0 fire\n4 sideways 0 fire\n", 4);
+        assert_error_line("seed 1\n4 down 0 jump\n", 2);
+        assert_error_line("seed 1\n4 down 0 fire 0\n", 2);
+        assert_error_line("seed 1\n4 analog 0.5\n", 2);
+        assert_error_line("seed 1\nsoon down 0 fire\n", 2);
+    }
+}
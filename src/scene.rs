@@ -0,0 +1,268 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::rc::Rc;
+use ggez::Context;
+use ggez::event::{Keycode, Button, Axis};
+use super::simulation::*;
+use super::renderer::*;
+use super::input;
+use super::input::Action;
+use super::key_bindings::*;
+use super::replay::*;
+use super::high_scores::*;
+use super::wave_definition::WaveSet;
+use super::collision_shape::SpriteHulls;
+use super::game_state::MAX_PLAYERS;
+
+pub enum SceneTransition {
+    None,
+    Push(Box<Scene>),
+    Pop,
+    Replace(Box<Scene>),
+    Reset(Box<Scene>)
+}
+
+pub trait Scene {
+    fn update(&mut self, _context: &mut SceneContext) -> SceneTransition {
+        SceneTransition::None
+    }
+
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, interpolation_value: f32);
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition;
+
+    fn button_event(&mut self, _context: &mut SceneContext, _button: Button, _pressed: bool, _controller: usize) -> SceneTransition {
+        SceneTransition::None
+    }
+
+    fn axis_event(&mut self, _context: &mut SceneContext, _axis: Axis, _value: f32, _controller: usize) {}
+
+    //Overlays are drawn on top of the scene below them instead of replacing it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    fn is_simulation_running(&self) -> bool {
+        false
+    }
+}
+
+pub struct SceneContext {
+    pub simulation: Simulation,
+    pub high_scores: HighScoreTable,
+    pub key_bindings: KeyBindings,
+    pub replay_mode: ReplayMode,
+    player_count: usize,
+    sticks: [(f32, f32); MAX_PLAYERS],
+    analog_moves: [(f32, f32); MAX_PLAYERS],
+    wave_set: WaveSet,
+    sprite_hulls: Rc<SpriteHulls>,
+    play_space_w: f32,
+    play_space_h: f32
+}
+
+impl SceneContext {
+    pub fn new(play_space_w: f32, play_space_h: f32, wave_set: WaveSet, sprite_hulls: Rc<SpriteHulls>, replay_mode: ReplayMode, high_scores: HighScoreTable, key_bindings: KeyBindings) -> SceneContext {
+        let (seed, player_count) = match replay_mode {
+            ReplayMode::Off => (Simulation::create_seed(), 1),
+            ReplayMode::Record(ref recorder) => (recorder.seed, 1),
+            ReplayMode::Playback(ref player) => (player.seed, player.player_count)
+        };
+        SceneContext {
+            simulation: Simulation::new(play_space_w, play_space_h, seed, wave_set.clone(), player_count, sprite_hulls.clone()),
+            high_scores,
+            key_bindings,
+            replay_mode,
+            player_count,
+            sticks: [(0.0, 0.0); MAX_PLAYERS],
+            analog_moves: [(0.0, 0.0); MAX_PLAYERS],
+            wave_set,
+            sprite_hulls,
+            play_space_w,
+            play_space_h
+        }
+    }
+
+    pub fn restart(&mut self) {
+        match self.replay_mode {
+            ReplayMode::Off => (),
+            _ => {
+                println!("Replay stopped, the new run is not recorded");
+                self.replay_mode = ReplayMode::Off;
+            }
+        }
+        self.simulation = Simulation::new(self.play_space_w, self.play_space_h, Simulation::create_seed(), self.wave_set.clone(), self.player_count, self.sprite_hulls.clone());
+        self.analog_moves = [(0.0, 0.0); MAX_PLAYERS];
+    }
+
+    //Swaps the idle title simulation for one with the chosen players, keeping its seed for recordings
+    pub fn start_run(&mut self, player_count: usize) {
+        self.player_count = player_count;
+        let seed = self.simulation.game_state.seed;
+        self.simulation = Simulation::new(self.play_space_w, self.play_space_h, seed, self.wave_set.clone(), player_count, self.sprite_hulls.clone());
+        self.analog_moves = [(0.0, 0.0); MAX_PLAYERS];
+        if let ReplayMode::Record(ref mut recorder) = self.replay_mode {
+            if let Err(e) = recorder.record_player_count(player_count) {
+                eprintln!("Failed to record replay player count: {}", e);
+            }
+        }
+    }
+
+    //Each controller drives its own player, extra controllers share the last one
+    fn get_controller_player(&self, controller: usize) -> usize {
+        controller.min(self.player_count.saturating_sub(1))
+    }
+
+    pub fn is_replay_playback(&self) -> bool {
+        match self.replay_mode {
+            ReplayMode::Playback(_) => true,
+            _ => false
+        }
+    }
+
+    fn apply_replay_events(&mut self) {
+        let mut replay_finished = false;
+        if let ReplayMode::Playback(ref mut player) = self.replay_mode {
+            while let Some(event) = player.next_due_event(self.simulation.game_state.tick) {
+                for input in event.input.get_simulation_inputs() {
+                    self.simulation.apply_input(input);
+                }
+            }
+            replay_finished = player.is_finished();
+        }
+        if replay_finished {
+            self.replay_mode = ReplayMode::Off;
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.apply_replay_events();
+        self.simulation.step();
+    }
+
+    pub fn apply_key_event(&mut self, keycode: Keycode, key_down: bool, repeat: bool) {
+        for (player, action) in self.key_bindings.get_actions(keycode) {
+            if player < self.player_count {
+                self.apply_action(player, action, key_down, repeat);
+            }
+        }
+    }
+
+    pub fn apply_button_event(&mut self, button: Button, pressed: bool, controller: usize) {
+        if let Some(action) = input::from_button(button) {
+            let player = self.get_controller_player(controller);
+            self.apply_action(player, action, pressed, false);
+        }
+    }
+
+    pub fn apply_axis_event(&mut self, axis: Axis, value: f32, controller: usize) {
+        let player = self.get_controller_player(controller);
+        match axis {
+            Axis::LeftX => self.sticks[player].0 = value,
+            Axis::LeftY => self.sticks[player].1 = value,
+            _ => return
+        }
+        let analog_move = input::apply_stick_dead_zone(self.sticks[player].0, self.sticks[player].1);
+        //Sticks report every tiny wobble, only changes that survive the dead zone are worth recording
+        if analog_move != self.analog_moves[player] {
+            self.analog_moves[player] = analog_move;
+            self.apply_replay_input(ReplayInput::AnalogMove(player, analog_move.0, analog_move.1));
+        }
+    }
+
+    //Replays record actions rather than keys so they play back the same under any bindings
+    pub fn apply_action(&mut self, player: usize, action: Action, pressed: bool, repeat: bool) {
+        self.apply_replay_input(ReplayInput::Action(player, action, pressed, repeat));
+    }
+
+    fn apply_replay_input(&mut self, replay_input: ReplayInput) {
+        if let ReplayMode::Record(ref mut recorder) = self.replay_mode {
+            let event = ReplayEvent {
+                tick: self.simulation.game_state.tick,
+                input: replay_input
+            };
+            if let Err(e) = recorder.record(event) {
+                eprintln!("Failed to record replay event: {}", e);
+            }
+        }
+        for input in replay_input.get_simulation_inputs() {
+            self.simulation.apply_input(input);
+        }
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<Scene>>
+}
+
+impl SceneStack {
+    pub fn new(initial_scene: Box<Scene>) -> SceneStack {
+        SceneStack {
+            scenes: vec![initial_scene]
+        }
+    }
+
+    fn apply_transition(&mut self, transition: SceneTransition) {
+        match transition {
+            SceneTransition::None => (),
+            SceneTransition::Push(scene) => self.scenes.push(scene),
+            SceneTransition::Pop => {
+                if self.scenes.len() > 1 {
+                    self.scenes.pop();
+                }
+            },
+            SceneTransition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            },
+            SceneTransition::Reset(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
+        }
+    }
+
+    pub fn update(&mut self, context: &mut SceneContext) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(context),
+            None => SceneTransition::None
+        };
+        self.apply_transition(transition);
+    }
+
+    pub fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.key_event(context, keycode, key_down, repeat),
+            None => SceneTransition::None
+        };
+        self.apply_transition(transition);
+    }
+
+    pub fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, controller: usize) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.button_event(context, button, pressed, controller),
+            None => SceneTransition::None
+        };
+        self.apply_transition(transition);
+    }
+
+    pub fn axis_event(&mut self, context: &mut SceneContext, axis: Axis, value: f32, controller: usize) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.axis_event(context, axis, value, controller);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, interpolation_value: f32) {
+        let first_drawn = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+        for scene in &self.scenes[first_drawn..] {
+            scene.draw(ctx, renderer, context, interpolation_value);
+        }
+    }
+
+    pub fn is_simulation_running(&self) -> bool {
+        self.scenes.last().map_or(false, |scene| scene.is_simulation_running())
+    }
+}
@@ -0,0 +1,554 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use ggez::Context;
+use ggez::event::{Keycode, Button, Axis};
+use super::scene::*;
+use super::renderer::*;
+use super::high_scores::*;
+use super::menu::*;
+use super::input::*;
+use super::key_bindings::*;
+use super::game_state::MAX_PLAYERS;
+
+fn is_press(keycode: Keycode, key_down: bool, repeat: bool, expected: Keycode) -> bool {
+    key_down && !repeat && keycode == expected
+}
+
+enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back
+}
+
+//Menus always answer to the arrow keys, Return and Escape on top of the bound actions
+fn menu_input_from_key(key_bindings: &KeyBindings, keycode: Keycode, key_down: bool) -> Option<MenuInput> {
+    if !key_down {
+        return None;
+    }
+    if keycode == Keycode::Up || key_bindings.is_bound(keycode, Action::MoveUp) {
+        Some(MenuInput::Up)
+    } else if keycode == Keycode::Down || key_bindings.is_bound(keycode, Action::MoveDown) {
+        Some(MenuInput::Down)
+    } else if keycode == Keycode::Left || key_bindings.is_bound(keycode, Action::MoveLeft) {
+        Some(MenuInput::Left)
+    } else if keycode == Keycode::Right || key_bindings.is_bound(keycode, Action::MoveRight) {
+        Some(MenuInput::Right)
+    } else if keycode == Keycode::Return || key_bindings.is_bound(keycode, Action::Fire) {
+        Some(MenuInput::Select)
+    } else if keycode == Keycode::Escape || key_bindings.is_bound(keycode, Action::Pause) {
+        Some(MenuInput::Back)
+    } else {
+        None
+    }
+}
+
+fn is_pause_press(key_bindings: &KeyBindings, keycode: Keycode, key_down: bool, repeat: bool) -> bool {
+    key_down && !repeat && key_bindings.is_bound(keycode, Action::Pause)
+}
+
+fn menu_input_from_button(button: Button, pressed: bool) -> Option<MenuInput> {
+    if !pressed {
+        return None;
+    }
+    match button {
+        Button::DPadUp => Some(MenuInput::Up),
+        Button::DPadDown => Some(MenuInput::Down),
+        Button::DPadLeft => Some(MenuInput::Left),
+        Button::DPadRight => Some(MenuInput::Right),
+        Button::A => Some(MenuInput::Select),
+        Button::B | Button::Start => Some(MenuInput::Back),
+        _ => None
+    }
+}
+
+pub struct TitleScene;
+
+impl TitleScene {
+    fn start_run(context: &mut SceneContext, player_count: usize) -> SceneTransition {
+        context.start_run(player_count);
+        SceneTransition::Replace(Box::new(PlayingScene))
+    }
+}
+
+impl Scene for TitleScene {
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, interpolation_value: f32) {
+        renderer.draw_world(ctx, &context.simulation.game_state, interpolation_value);
+        renderer.draw_title(ctx);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if !key_down || repeat {
+            return SceneTransition::None;
+        }
+        match keycode {
+            Keycode::Space => TitleScene::start_run(context, 1),
+            Keycode::Num2 => TitleScene::start_run(context, 2),
+            Keycode::H => SceneTransition::Push(Box::new(HighScoresScene)),
+            Keycode::O => SceneTransition::Push(Box::new(OptionsScene::new())),
+            _ => SceneTransition::None
+        }
+    }
+
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, _controller: usize) -> SceneTransition {
+        if !pressed {
+            return SceneTransition::None;
+        }
+        match button {
+            Button::Start => TitleScene::start_run(context, 1),
+            Button::X => TitleScene::start_run(context, 2),
+            Button::Y => SceneTransition::Push(Box::new(HighScoresScene)),
+            Button::Back => SceneTransition::Push(Box::new(OptionsScene::new())),
+            _ => match menu_input_from_button(button, pressed) {
+                Some(MenuInput::Select) => TitleScene::start_run(context, 1),
+                _ => SceneTransition::None
+            }
+        }
+    }
+}
+
+pub struct PlayingScene;
+
+impl PlayingScene {
+    fn check_run_state(context: &SceneContext) -> SceneTransition {
+        if context.simulation.is_game_over() {
+            SceneTransition::Reset(Box::new(GameOverScene::new()))
+        } else if context.simulation.is_wave_complete() {
+            SceneTransition::Push(Box::new(WaveClearScene))
+        } else {
+            SceneTransition::None
+        }
+    }
+}
+
+impl Scene for PlayingScene {
+    fn update(&mut self, context: &mut SceneContext) -> SceneTransition {
+        context.step();
+        PlayingScene::check_run_state(context)
+    }
+
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, interpolation_value: f32) {
+        renderer.draw_world(ctx, &context.simulation.game_state, interpolation_value);
+        renderer.draw_hud(ctx, &context.simulation);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if is_pause_press(&context.key_bindings, keycode, key_down, repeat) {
+            return SceneTransition::Push(Box::new(PauseMenuScene::new()));
+        }
+        context.apply_key_event(keycode, key_down, repeat);
+        SceneTransition::None
+    }
+
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, controller: usize) -> SceneTransition {
+        if pressed && from_button(button) == Some(Action::Pause) {
+            return SceneTransition::Push(Box::new(PauseMenuScene::new()));
+        }
+        context.apply_button_event(button, pressed, controller);
+        SceneTransition::None
+    }
+
+    fn axis_event(&mut self, context: &mut SceneContext, axis: Axis, value: f32, controller: usize) {
+        context.apply_axis_event(axis, value, controller);
+    }
+
+    fn is_simulation_running(&self) -> bool {
+        true
+    }
+}
+
+pub struct WaveClearScene;
+
+impl Scene for WaveClearScene {
+    fn update(&mut self, context: &mut SceneContext) -> SceneTransition {
+        context.step();
+        if context.simulation.is_game_over() {
+            SceneTransition::Reset(Box::new(GameOverScene::new()))
+        } else if !context.simulation.is_wave_complete() {
+            SceneTransition::Pop
+        } else {
+            SceneTransition::None
+        }
+    }
+
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_wave_clear(ctx, &context.simulation);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if is_pause_press(&context.key_bindings, keycode, key_down, repeat) {
+            return SceneTransition::Push(Box::new(PauseMenuScene::new()));
+        }
+        context.apply_key_event(keycode, key_down, repeat);
+        SceneTransition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, controller: usize) -> SceneTransition {
+        if pressed && from_button(button) == Some(Action::Pause) {
+            return SceneTransition::Push(Box::new(PauseMenuScene::new()));
+        }
+        context.apply_button_event(button, pressed, controller);
+        SceneTransition::None
+    }
+
+    fn axis_event(&mut self, context: &mut SceneContext, axis: Axis, value: f32, controller: usize) {
+        context.apply_axis_event(axis, value, controller);
+    }
+
+    fn is_simulation_running(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Options,
+    QuitToTitle
+}
+
+pub struct PauseMenuScene {
+    menu: Menu<PauseAction>
+}
+
+impl PauseMenuScene {
+    pub fn new() -> PauseMenuScene {
+        PauseMenuScene {
+            menu: Menu::new(vec![
+                (PauseAction::Resume, "Resume"),
+                (PauseAction::Restart, "Restart Run"),
+                (PauseAction::Options, "Options"),
+                (PauseAction::QuitToTitle, "Quit to Title")
+            ])
+        }
+    }
+
+    fn handle_menu_input(&mut self, context: &mut SceneContext, menu_input: MenuInput) -> SceneTransition {
+        match menu_input {
+            MenuInput::Up => self.menu.select_previous(),
+            MenuInput::Down => self.menu.select_next(),
+            MenuInput::Left | MenuInput::Right => (),
+            MenuInput::Back => return SceneTransition::Pop,
+            MenuInput::Select => return match self.menu.get_selected() {
+                Some(PauseAction::Resume) => SceneTransition::Pop,
+                Some(PauseAction::Restart) => {
+                    context.restart();
+                    SceneTransition::Reset(Box::new(PlayingScene))
+                },
+                Some(PauseAction::Options) => SceneTransition::Push(Box::new(OptionsScene::new())),
+                Some(PauseAction::QuitToTitle) => {
+                    context.restart();
+                    SceneTransition::Reset(Box::new(TitleScene))
+                },
+                None => SceneTransition::None
+            }
+        }
+        SceneTransition::None
+    }
+}
+
+impl Scene for PauseMenuScene {
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, _context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_pause_menu(ctx, &self.menu.get_labels(), self.menu.get_selected_index());
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        //Releases still reach the simulation so keys let go while paused don't stay held
+        if !key_down {
+            context.apply_key_event(keycode, key_down, repeat);
+        }
+        match menu_input_from_key(&context.key_bindings, keycode, key_down) {
+            Some(menu_input) => self.handle_menu_input(context, menu_input),
+            None => SceneTransition::None
+        }
+    }
+
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, controller: usize) -> SceneTransition {
+        if !pressed {
+            context.apply_button_event(button, pressed, controller);
+        }
+        match menu_input_from_button(button, pressed) {
+            Some(menu_input) => self.handle_menu_input(context, menu_input),
+            None => SceneTransition::None
+        }
+    }
+
+    fn axis_event(&mut self, context: &mut SceneContext, axis: Axis, value: f32, controller: usize) {
+        context.apply_axis_event(axis, value, controller);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+pub struct GameOverScene {
+    high_score_checked: bool
+}
+
+impl GameOverScene {
+    pub fn new() -> GameOverScene {
+        GameOverScene {
+            high_score_checked: false
+        }
+    }
+
+    fn play_again(context: &mut SceneContext) -> SceneTransition {
+        context.restart();
+        SceneTransition::Reset(Box::new(PlayingScene))
+    }
+
+    fn quit_to_title(context: &mut SceneContext) -> SceneTransition {
+        context.restart();
+        SceneTransition::Reset(Box::new(TitleScene))
+    }
+}
+
+impl Scene for GameOverScene {
+    fn update(&mut self, context: &mut SceneContext) -> SceneTransition {
+        if self.high_score_checked {
+            return SceneTransition::None;
+        }
+        self.high_score_checked = true;
+        if context.is_replay_playback() {
+            return SceneTransition::None;
+        }
+        let wave = context.simulation.wave_manager.get_wave_level();
+        let name_entries: Vec<(usize, NameEntry)> = context.simulation.game_state.players.iter()
+            .enumerate()
+            .map(|(player, state)| (player, NameEntry::new(state.score.get_total(), wave)))
+            .collect();
+        match NameEntryScene::next(context, name_entries) {
+            Some(name_entry_scene) => SceneTransition::Push(Box::new(name_entry_scene)),
+            None => SceneTransition::None
+        }
+    }
+
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_world(ctx, &context.simulation.game_state, 0.0);
+        renderer.draw_hud(ctx, &context.simulation);
+        renderer.draw_game_over(ctx);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if !key_down || repeat {
+            return SceneTransition::None;
+        }
+        match keycode {
+            Keycode::Space => GameOverScene::play_again(context),
+            Keycode::Escape => GameOverScene::quit_to_title(context),
+            _ => SceneTransition::None
+        }
+    }
+
+    //START plays again here rather than backing out like it does in the menus
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, _controller: usize) -> SceneTransition {
+        if pressed && button == Button::Start {
+            return GameOverScene::play_again(context);
+        }
+        match menu_input_from_button(button, pressed) {
+            Some(MenuInput::Select) => GameOverScene::play_again(context),
+            Some(MenuInput::Back) => GameOverScene::quit_to_title(context),
+            _ => SceneTransition::None
+        }
+    }
+}
+
+pub struct NameEntryScene {
+    player: Option<usize>,
+    name_entry: NameEntry,
+    pending: Vec<(usize, NameEntry)>
+}
+
+impl NameEntryScene {
+    //Co-op players enter initials one after another, each checked against the table as it stands by then
+    pub fn next(context: &SceneContext, mut name_entries: Vec<(usize, NameEntry)>) -> Option<NameEntryScene> {
+        let show_player = context.simulation.get_player_count() > 1;
+        name_entries.retain(|&(_, ref name_entry)| context.high_scores.qualifies(name_entry.score));
+        if name_entries.is_empty() {
+            return None;
+        }
+        let (player, name_entry) = name_entries.remove(0);
+        Some(NameEntryScene {
+            player: if show_player { Some(player) } else { None },
+            name_entry,
+            pending: name_entries
+        })
+    }
+
+    fn submit(&mut self, context: &mut SceneContext) -> SceneTransition {
+        context.high_scores.insert(self.name_entry.create_entry());
+        if let Err(e) = context.high_scores.save() {
+            eprintln!("Failed to save high scores: {}", e);
+        }
+        let pending = self.pending.drain(..).collect();
+        match NameEntryScene::next(context, pending) {
+            Some(name_entry_scene) => SceneTransition::Replace(Box::new(name_entry_scene)),
+            None => SceneTransition::Replace(Box::new(HighScoresScene))
+        }
+    }
+
+    fn confirm_slot(&mut self, context: &mut SceneContext) -> SceneTransition {
+        if self.name_entry.is_last_slot() {
+            return self.submit(context);
+        }
+        self.name_entry.next_slot();
+        SceneTransition::None
+    }
+}
+
+impl Scene for NameEntryScene {
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, _context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_name_entry(ctx, &self.name_entry, self.player);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, _repeat: bool) -> SceneTransition {
+        if !key_down {
+            return SceneTransition::None;
+        }
+        match keycode {
+            Keycode::W | Keycode::Up => self.name_entry.next_letter(),
+            Keycode::S | Keycode::Down => self.name_entry.previous_letter(),
+            Keycode::D | Keycode::Right => self.name_entry.next_slot(),
+            Keycode::A | Keycode::Left => self.name_entry.previous_slot(),
+            Keycode::Space | Keycode::Return => return self.confirm_slot(context),
+            _ => ()
+        }
+        SceneTransition::None
+    }
+
+    fn button_event(&mut self, context: &mut SceneContext, button: Button, pressed: bool, _controller: usize) -> SceneTransition {
+        if pressed && button == Button::Start {
+            return self.confirm_slot(context);
+        }
+        match menu_input_from_button(button, pressed) {
+            Some(MenuInput::Up) => self.name_entry.next_letter(),
+            Some(MenuInput::Down) => self.name_entry.previous_letter(),
+            Some(MenuInput::Right) => self.name_entry.next_slot(),
+            Some(MenuInput::Left) | Some(MenuInput::Back) => self.name_entry.previous_slot(),
+            Some(MenuInput::Select) => return self.confirm_slot(context),
+            None => ()
+        }
+        SceneTransition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+pub struct HighScoresScene;
+
+impl Scene for HighScoresScene {
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_high_scores(ctx, &context.high_scores);
+    }
+
+    fn key_event(&mut self, _context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if is_press(keycode, key_down, repeat, Keycode::Space) || is_press(keycode, key_down, repeat, Keycode::Escape) {
+            SceneTransition::Pop
+        } else {
+            SceneTransition::None
+        }
+    }
+
+    fn button_event(&mut self, _context: &mut SceneContext, button: Button, pressed: bool, _controller: usize) -> SceneTransition {
+        match menu_input_from_button(button, pressed) {
+            Some(MenuInput::Select) | Some(MenuInput::Back) => SceneTransition::Pop,
+            _ => SceneTransition::None
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+pub struct OptionsScene {
+    menu: Menu<Action>,
+    player: usize,
+    awaiting_key: bool
+}
+
+impl OptionsScene {
+    pub fn new() -> OptionsScene {
+        OptionsScene {
+            menu: Menu::new(ACTIONS.iter().map(|action| (*action, action.get_label())).collect()),
+            player: 0,
+            awaiting_key: false
+        }
+    }
+
+    fn save_key_bindings(context: &SceneContext) {
+        if let Err(e) = context.key_bindings.save() {
+            eprintln!("Failed to save key bindings: {}", e);
+        }
+    }
+
+    fn handle_menu_input(&mut self, menu_input: MenuInput) -> SceneTransition {
+        match menu_input {
+            MenuInput::Up => self.menu.select_previous(),
+            MenuInput::Down => self.menu.select_next(),
+            MenuInput::Left => self.player = self.player.saturating_sub(1),
+            MenuInput::Right => self.player = (self.player + 1).min(MAX_PLAYERS - 1),
+            MenuInput::Select => self.awaiting_key = true,
+            MenuInput::Back => return SceneTransition::Pop
+        }
+        SceneTransition::None
+    }
+}
+
+impl Scene for OptionsScene {
+    fn draw(&self, ctx: &mut Context, renderer: &Renderer, context: &SceneContext, _interpolation_value: f32) {
+        renderer.draw_options(ctx, &context.key_bindings, self.player, self.menu.get_selected_index(), self.awaiting_key);
+    }
+
+    fn key_event(&mut self, context: &mut SceneContext, keycode: Keycode, key_down: bool, repeat: bool) -> SceneTransition {
+        if !key_down || repeat {
+            return SceneTransition::None;
+        }
+        if self.awaiting_key {
+            self.awaiting_key = false;
+            if keycode != Keycode::Escape {
+                if let Some(action) = self.menu.get_selected() {
+                    context.key_bindings.rebind(self.player, action, keycode);
+                    OptionsScene::save_key_bindings(context);
+                }
+            }
+            return SceneTransition::None;
+        }
+        if keycode == Keycode::R {
+            context.key_bindings = KeyBindings::default();
+            OptionsScene::save_key_bindings(context);
+            return SceneTransition::None;
+        }
+        match menu_input_from_key(&context.key_bindings, keycode, key_down) {
+            Some(menu_input) => self.handle_menu_input(menu_input),
+            None => SceneTransition::None
+        }
+    }
+
+    fn button_event(&mut self, _context: &mut SceneContext, button: Button, pressed: bool, _controller: usize) -> SceneTransition {
+        if self.awaiting_key {
+            return SceneTransition::None;
+        }
+        match menu_input_from_button(button, pressed) {
+            Some(menu_input) => self.handle_menu_input(menu_input),
+            None => SceneTransition::None
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
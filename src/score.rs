@@ -0,0 +1,98 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use nalgebra::Vector2;
+use super::simulation::ms_to_ticks;
+
+const COMBO_WINDOW: u64 = 1500;
+const KILLS_PER_MULTIPLIER: u32 = 5;
+const MAX_MULTIPLIER: u32 = 8;
+const MISS_STREAK_LIMIT: u32 = 6;
+const POPUP_TIME: u64 = 800;
+const POPUP_RISE_SPEED: f32 = 1.0;
+
+pub struct ScorePopup {
+    pub pos: Vector2<f32>,
+    pub value: u32,
+    age: u64
+}
+
+impl ScorePopup {
+    pub fn get_alpha(&self) -> f32 {
+        1.0 - (self.age as f32 / ms_to_ticks(POPUP_TIME) as f32)
+    }
+}
+
+pub struct Score {
+    total: u32,
+    chain: u32,
+    last_kill: u64,
+    miss_streak: u32,
+    popups: Vec<ScorePopup>
+}
+
+impl Score {
+    pub fn new() -> Score {
+        Score {
+            total: 0,
+            chain: 0,
+            last_kill: 0,
+            miss_streak: 0,
+            popups: vec![]
+        }
+    }
+
+    pub fn get_total(&self) -> u32 {
+        self.total
+    }
+
+    pub fn get_multiplier(&self) -> u32 {
+        (1 + self.chain / KILLS_PER_MULTIPLIER).min(MAX_MULTIPLIER)
+    }
+
+    pub fn get_popups(&self) -> &Vec<ScorePopup> {
+        &self.popups
+    }
+
+    pub fn add_kill(&mut self, value: u32, pos: Vector2<f32>, tick: u64) {
+        if tick - self.last_kill > ms_to_ticks(COMBO_WINDOW) {
+            self.chain = 0;
+        }
+        self.chain += 1;
+        self.last_kill = tick;
+        self.miss_streak = 0;
+        let awarded = value * self.get_multiplier();
+        self.total += awarded;
+        self.popups.push(ScorePopup {
+            pos,
+            value: awarded,
+            age: 0
+        });
+    }
+
+    pub fn register_miss(&mut self) {
+        self.miss_streak += 1;
+        if self.miss_streak >= MISS_STREAK_LIMIT {
+            self.reset_combo();
+        }
+    }
+
+    pub fn reset_combo(&mut self) {
+        self.chain = 0;
+        self.miss_streak = 0;
+    }
+
+    pub fn update(&mut self, tick: u64) {
+        if self.chain > 0 && tick - self.last_kill > ms_to_ticks(COMBO_WINDOW) {
+            self.reset_combo();
+        }
+        for popup in &mut self.popups {
+            popup.age += 1;
+            popup.pos.y += POPUP_RISE_SPEED;
+        }
+        let popup_ticks = ms_to_ticks(POPUP_TIME);
+        self.popups.retain(|popup| popup.age < popup_ticks);
+    }
+}
@@ -0,0 +1,89 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::entity_manager::*;
+use super::wave_manager::*;
+use super::play_space::*;
+use super::input::*;
+use super::wave_definition::WaveSet;
+use super::collision_shape::SpriteHulls;
+use super::game_state::GameState;
+
+pub const TICKS_PER_SECOND: u64 = 60;
+const RESPAWN_TIME: u64 = 700;
+
+pub fn ms_to_ticks(ms: u64) -> u64 {
+    (ms * TICKS_PER_SECOND) / 1000
+}
+
+pub struct Simulation {
+    pub game_state: GameState,
+    pub wave_manager: WaveManager
+}
+
+impl Simulation {
+    pub fn new(play_space_w: f32, play_space_h: f32, seed: u32, wave_set: WaveSet, player_count: usize, sprite_hulls: Rc<SpriteHulls>) -> Simulation {
+        let play_space = PlaySpace::new(play_space_w, play_space_h);
+        Simulation {
+            wave_manager: WaveManager::new(play_space.clone(), wave_set),
+            game_state: GameState::new(play_space, seed, player_count, sprite_hulls)
+        }
+    }
+
+    pub fn create_seed() -> u32 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs() as u32 ^ since_epoch.subsec_nanos(),
+            Err(_) => 0
+        }
+    }
+
+    pub fn step(&mut self) {
+        if !self.is_game_over() {
+            for player in 0..self.game_state.players.len() {
+                if EntityManager::can_player_respawn(&self.game_state, player) && self.game_state.tick - EntityManager::get_player_last_death(&self.game_state, player) > ms_to_ticks(RESPAWN_TIME) {
+                    EntityManager::respawn_player(&mut self.game_state, player)
+                }
+            }
+            EntityManager::update(&mut self.game_state);
+            self.wave_manager.update(&mut self.game_state);
+
+            let escapes = EntityManager::update_life_lost(&mut self.game_state);
+            EntityManager::lose_lives(&mut self.game_state, &escapes);
+            self.game_state.tick += 1;
+        }
+    }
+
+    pub fn apply_input(&mut self, input: SimulationInput) {
+        //Inputs for a second player are dropped when only one is playing
+        if input.get_player().map_or(false, |player| player >= self.game_state.players.len()) {
+            return;
+        }
+        match input {
+            SimulationInput::PlayerMove(player, dir) => EntityManager::player_move(&mut self.game_state, player, dir),
+            SimulationInput::PlayerMoveCancel(player, dir) => EntityManager::player_move_cancel(&mut self.game_state, player, dir),
+            SimulationInput::PlayerAnalogMove(player, x, y) => EntityManager::player_analog_move(&mut self.game_state, player, x, y),
+            SimulationInput::PlayerFire(player) => EntityManager::player_fire(&mut self.game_state, player),
+            SimulationInput::PlayerFireCancel(player) => EntityManager::player_fire_cancel(&mut self.game_state, player),
+            SimulationInput::PlayerBomb(player) => if !self.is_game_over() {
+                EntityManager::player_bomb(&mut self.game_state, player)
+            },
+            SimulationInput::Confirm => if self.is_wave_complete() {
+                self.wave_manager.set_to_progress_level();
+            }
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool { self.game_state.players.iter().all(|state| state.is_out()) }
+
+    pub fn get_player_count(&self) -> usize {
+        self.game_state.players.len()
+    }
+
+    pub fn is_wave_complete(&self) -> bool {
+        self.wave_manager.wave_spawn_complete() && EntityManager::get_enemy_count(&self.game_state) == 0 && !EntityManager::is_boss_active(&self.game_state)
+    }
+}
@@ -0,0 +1,233 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use nalgebra::{Vector2, Point, U2};
+use ncollide::bounding_volume::{AABB, BoundingVolume};
+use super::arena::EntityId;
+
+type CellRange = (usize, usize, usize, usize); //min column, min row, max column, max row
+
+struct Placement {
+    id: EntityId,
+    area: AABB<Point<f32, U2>>,
+    cells: CellRange,
+    update_mark: u32,
+    query_mark: u32
+}
+
+//Uniform grid over the entity area used as the collision broadphase. Entities stay in their cells
+//between ticks and are only moved when their area crosses into different cells.
+pub struct SpatialGrid {
+    origin: Vector2<f32>,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<EntityId>>,
+    placements: Vec<Option<Placement>>, //by id index
+    update_mark: u32,
+    query_mark: u32
+}
+
+impl SpatialGrid {
+    pub fn new(origin: Vector2<f32>, size: Vector2<f32>, cell_size: f32) -> SpatialGrid {
+        let columns = ((size.x / cell_size).ceil() as usize).max(1);
+        let rows = ((size.y / cell_size).ceil() as usize).max(1);
+        SpatialGrid {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows],
+            placements: vec![],
+            update_mark: 0,
+            query_mark: 0
+        }
+    }
+
+    //Anything outside the grid is clamped into the border cells, so overlaps there are still found
+    fn get_cell_range(&self, area: &AABB<Point<f32, U2>>) -> CellRange {
+        let to_column = |x: f32| (((x - self.origin.x) / self.cell_size).floor().max(0.0) as usize).min(self.columns - 1);
+        let to_row = |y: f32| (((y - self.origin.y) / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1);
+        let mins = area.mins();
+        let maxs = area.maxs();
+        (to_column(mins.x), to_row(mins.y), to_column(maxs.x), to_row(maxs.y))
+    }
+
+    fn add_to_cells(&mut self, id: EntityId, (min_column, min_row, max_column, max_row): CellRange) {
+        for row in min_row..(max_row + 1) {
+            for column in min_column..(max_column + 1) {
+                self.cells[row * self.columns + column].push(id);
+            }
+        }
+    }
+
+    fn remove_from_cells(&mut self, id: EntityId, (min_column, min_row, max_column, max_row): CellRange) {
+        for row in min_row..(max_row + 1) {
+            for column in min_column..(max_column + 1) {
+                self.cells[row * self.columns + column].retain(|other| *other != id);
+            }
+        }
+    }
+
+    fn remove_placement(&mut self, index: usize) {
+        if let Some(placement) = self.placements[index].take() {
+            self.remove_from_cells(placement.id, placement.cells);
+        }
+    }
+
+    fn place(&mut self, id: EntityId, area: AABB<Point<f32, U2>>) {
+        let index = id.get_index();
+        while self.placements.len() <= index {
+            self.placements.push(None);
+        }
+        let cells = self.get_cell_range(&area);
+        let previous = self.placements[index].as_ref().map(|placement| (placement.id, placement.cells));
+        let moved = match previous {
+            Some((previous_id, previous_cells)) if previous_id == id && previous_cells == cells => false,
+            Some((previous_id, previous_cells)) => {
+                self.remove_from_cells(previous_id, previous_cells);
+                true
+            },
+            None => true
+        };
+        if moved {
+            self.add_to_cells(id, cells);
+        }
+        self.placements[index] = Some(Placement {
+            id,
+            area,
+            cells,
+            update_mark: self.update_mark,
+            query_mark: 0
+        });
+    }
+
+    //Brings the grid up to date with this tick's areas, dropping anything that is no longer listed
+    pub fn update<I: IntoIterator<Item = (EntityId, Option<AABB<Point<f32, U2>>>)>>(&mut self, areas: I) {
+        self.update_mark = self.update_mark.wrapping_add(1);
+        for (id, area) in areas {
+            match area {
+                Some(area) => self.place(id, area),
+                None => self.remove_placement(id.get_index())
+            }
+        }
+        for index in 0..self.placements.len() {
+            let stale = self.placements[index].as_ref().map_or(false, |placement| placement.update_mark != self.update_mark);
+            if stale {
+                self.remove_placement(index);
+            }
+        }
+    }
+
+    //Fills found with every id whose area overlaps the given one, sorted so callers resolve hits in a stable order
+    pub fn query(&mut self, area: &AABB<Point<f32, U2>>, found: &mut Vec<EntityId>) {
+        found.clear();
+        self.query_mark = self.query_mark.wrapping_add(1);
+        if self.query_mark == 0 {
+            for placement in self.placements.iter_mut().flat_map(|placement| placement.as_mut()) {
+                placement.query_mark = 0;
+            }
+            self.query_mark = 1;
+        }
+        let (min_column, min_row, max_column, max_row) = self.get_cell_range(area);
+        for row in min_row..(max_row + 1) {
+            for column in min_column..(max_column + 1) {
+                for id in &self.cells[row * self.columns + column] {
+                    if let Some(ref mut placement) = self.placements[id.get_index()] {
+                        if placement.query_mark != self.query_mark {
+                            placement.query_mark = self.query_mark;
+                            if placement.area.intersects(area) {
+                                found.push(*id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        found.sort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point2;
+    use super::super::arena::Arena;
+
+    fn create_area(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> AABB<Point<f32, U2>> {
+        AABB::new(Point2::new(min_x, min_y), Point2::new(max_x, max_y))
+    }
+
+    fn create_grid() -> SpatialGrid {
+        SpatialGrid::new(Vector2::new(0.0, 0.0), Vector2::new(400.0, 400.0), 100.0)
+    }
+
+    fn query(grid: &mut SpatialGrid, area: AABB<Point<f32, U2>>) -> Vec<EntityId> {
+        let mut found = vec![];
+        grid.query(&area, &mut found);
+        found
+    }
+
+    fn count_placements(grid: &SpatialGrid, id: EntityId) -> usize {
+        grid.cells.iter().map(|cell| cell.iter().filter(|other| **other == id).count()).sum()
+    }
+
+    #[test]
+    fn moved_entity_leaves_its_old_cells() {
+        let mut arena = Arena::new();
+        let id = arena.insert(0);
+        let mut grid = create_grid();
+        grid.update(vec![(id, Some(create_area(10.0, 10.0, 20.0, 20.0)))]);
+        grid.update(vec![(id, Some(create_area(310.0, 310.0, 320.0, 320.0)))]);
+        assert!(query(&mut grid, create_area(0.0, 0.0, 99.0, 99.0)).is_empty());
+        assert_eq!(query(&mut grid, create_area(300.0, 300.0, 399.0, 399.0)), vec![id]);
+        assert_eq!(count_placements(&grid, id), 1);
+    }
+
+    #[test]
+    fn removed_entity_is_not_found() {
+        let mut arena = Arena::new();
+        let kept = arena.insert(0);
+        let removed = arena.insert(1);
+        let mut grid = create_grid();
+        let area = create_area(10.0, 10.0, 20.0, 20.0);
+        grid.update(vec![(kept, Some(area.clone())), (removed, Some(area.clone()))]);
+        grid.update(vec![(kept, Some(area.clone()))]);
+        assert_eq!(query(&mut grid, area.clone()), vec![kept]);
+        grid.update(vec![(kept, None)]);
+        assert!(query(&mut grid, area).is_empty());
+        assert_eq!(count_placements(&grid, kept) + count_placements(&grid, removed), 0);
+    }
+
+    #[test]
+    fn reused_slot_replaces_the_old_generation() {
+        let mut arena = Arena::new();
+        let old = arena.insert(0);
+        arena.retain(|_| false);
+        let reused = arena.insert(1);
+        assert_eq!(old.get_index(), reused.get_index());
+        let mut grid = create_grid();
+        grid.update(vec![(old, Some(create_area(10.0, 10.0, 20.0, 20.0)))]);
+        grid.update(vec![(reused, Some(create_area(10.0, 10.0, 20.0, 20.0)))]);
+        assert_eq!(query(&mut grid, create_area(0.0, 0.0, 400.0, 400.0)), vec![reused]);
+        assert_eq!(count_placements(&grid, old), 0);
+        assert_eq!(count_placements(&grid, reused), 1);
+    }
+
+    #[test]
+    fn entity_spanning_cells_is_found_once_in_id_order() {
+        let mut arena = Arena::new();
+        let ids: Vec<EntityId> = (0..3).map(|value| arena.insert(value)).collect();
+        let mut grid = create_grid();
+        grid.update(vec![
+            (ids[2], Some(create_area(250.0, 250.0, 260.0, 260.0))),
+            (ids[1], Some(create_area(50.0, 50.0, 250.0, 250.0))),
+            (ids[0], Some(create_area(150.0, 150.0, 160.0, 160.0)))
+        ]);
+        assert_eq!(count_placements(&grid, ids[1]), 9);
+        assert_eq!(query(&mut grid, create_area(0.0, 0.0, 400.0, 400.0)), ids);
+        assert_eq!(query(&mut grid, create_area(0.0, 0.0, 400.0, 400.0)), ids);
+    }
+}
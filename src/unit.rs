@@ -4,17 +4,18 @@ Please see the file LICENSE in this distribution
 for license terms. */
 
 use std::f32;
-use ggez::*;
 use nalgebra::Vector2;
-use nalgebra::Point2;
 use super::entity::*;
 use super::body::*;
-use super::asset_manager::*;
-use super::camera::*;
+
+const HIT_FLASH_TICKS: u32 = 6;
 
 pub struct Unit {
     pub is_dead: bool,
     pub asset_key: String,
+    pub hit_points: u32,
+    pub max_hit_points: u32,
+    hit_flash: u32,
     pub body: Body,
     sheet_w: u32,
     sheet_h: u32,
@@ -27,6 +28,9 @@ impl Unit {
         Unit {
             is_dead: false,
             asset_key,
+            hit_points: 1,
+            max_hit_points: 1,
+            hit_flash: 0,
             sheet_w,
             sheet_h,
             animation_repeats,
@@ -35,21 +39,13 @@ impl Unit {
         }
     }
 
-    fn get_default_draw_param(&self, interpolation_value: f32, camera: &Camera) -> graphics::DrawParam  {
-        let view_position = self.body.get_view_position(interpolation_value, camera);
-        graphics::DrawParam {
-            src: graphics::Rect {
-                x: (self.animation_progress % self.sheet_w) as f32 / self.sheet_w as f32,
-                y: (self.animation_progress / self.sheet_w) as f32 / self.sheet_h as f32,
-                w: 1.0/self.sheet_w as f32,
-                h: 1.0/self.sheet_h as f32
-            },
-            dest: Point2::new(view_position.x, view_position.y),
-            rotation: self.body.rotation,
-            scale: Point2::new(self.body.scale.x, self.body.scale.y),
-            offset: Point2::new(0.5, 0.5),
-            .. Default::default()
-        }
+    pub fn get_animation_frame(&self) -> [f32; 4] { //x, y, width, height
+        [
+            (self.animation_progress % self.sheet_w) as f32 / self.sheet_w as f32,
+            (self.animation_progress / self.sheet_w) as f32 / self.sheet_h as f32,
+            1.0/self.sheet_w as f32,
+            1.0/self.sheet_h as f32
+        ]
     }
 
     fn update_animation(&mut self) {
@@ -68,18 +64,33 @@ impl Unit {
     pub fn set_velocity(&mut self, velocity: Vector2<f32>) {
         self.body.velocity = velocity;
     }
+
+    pub fn set_max_hit_points(&mut self, hit_points: u32) {
+        self.max_hit_points = hit_points.max(1);
+        self.hit_points = self.max_hit_points;
+    }
+
+    pub fn restore_hit_points(&mut self) {
+        self.hit_points = self.max_hit_points;
+        self.hit_flash = 0;
+    }
+
+    pub fn is_hit_flashing(&self) -> bool {
+        self.hit_flash > 0
+    }
 }
 
 impl Entity for Unit {
     fn update(&mut self) {
+        if self.hit_flash > 0 {
+            self.hit_flash -= 1;
+        }
         self.update_animation();
         self.body.update_pos();
     }
 
-    fn draw(&self, asset_manager: &AssetManager, ctx: &mut Context, interpolation_value: f32, camera: &Camera) {
-        if !self.is_dead {
-            asset_manager.draw_asset(self.asset_key.clone(), ctx, self.get_default_draw_param(interpolation_value, camera));
-        }
+    fn get_unit(&self) -> &Unit {
+        self
     }
 
     fn get_body(&self) -> Body {
@@ -94,6 +105,20 @@ impl Entity for Unit {
         self.is_dead
     }
 
+    fn take_damage(&mut self, damage: u32) -> bool {
+        if self.is_dead {
+            return false;
+        }
+        self.hit_points = self.hit_points.saturating_sub(damage);
+        self.hit_flash = HIT_FLASH_TICKS;
+        if self.hit_points == 0 {
+            self.set_dead();
+            true
+        } else {
+            false
+        }
+    }
+
     fn set_dead(&mut self) {
         self.is_dead = true;
     }
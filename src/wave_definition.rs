@@ -0,0 +1,231 @@
+/* Copyright (c) 2018 Ashton Hunger
+This work is available under the "MIT License”.
+Please see the file LICENSE in this distribution
+for license terms. */
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use toml;
+use super::enemy::*;
+
+const DEFAULT_SPAWN_DELAY_MS: u64 = 1000;
+const MAX_SPAWN_DELAY_MS: u64 = 60_000;
+const MAX_GROUP_COUNT: u32 = 200;
+pub const MAX_MODIFIER: f32 = 10.0;
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum Formation {
+    Random,
+    Line,
+    Column,
+    Vee
+}
+
+impl Default for Formation {
+    fn default() -> Formation {
+        Formation::Random
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct WaveModifiers {
+    pub speed: f32,
+    pub health: f32,
+    pub fire_rate: f32
+}
+
+impl Default for WaveModifiers {
+    fn default() -> WaveModifiers {
+        WaveModifiers {
+            speed: 1.0,
+            health: 1.0,
+            fire_rate: 1.0
+        }
+    }
+}
+
+fn default_spawn_delay_ms() -> u64 {
+    DEFAULT_SPAWN_DELAY_MS
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnGroup {
+    pub enemy: EnemyType,
+    pub count: u32,
+    #[serde(default)]
+    pub start_delay_ms: u64,
+    #[serde(default = "default_spawn_delay_ms")]
+    pub spawn_delay_ms: u64,
+    #[serde(default)]
+    pub formation: Formation,
+    #[serde(default)]
+    pub position: Option<f32> //0.0 bottom of the spawn range, 1.0 top
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WaveDefinition {
+    #[serde(default)]
+    pub modifiers: WaveModifiers,
+    pub groups: Vec<SpawnGroup>
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WaveSet {
+    #[serde(default)]
+    pub waves: Vec<WaveDefinition>
+}
+
+#[derive(Debug)]
+pub enum WaveLoadError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Invalid(usize, usize, String) //wave, group, message
+}
+
+impl fmt::Display for WaveLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WaveLoadError::Io(ref e) => write!(f, "failed to read wave file: {}", e),
+            WaveLoadError::Parse(ref e) => write!(f, "failed to parse wave file: {}", e),
+            WaveLoadError::Invalid(wave, group, ref message) => write!(f, "wave {} group {}: {}", wave, group, message)
+        }
+    }
+}
+
+impl WaveSet {
+    pub fn load(path: &Path) -> Result<WaveSet, WaveLoadError> {
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(WaveLoadError::Io)?;
+        WaveSet::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<WaveSet, WaveLoadError> {
+        let wave_set: WaveSet = toml::from_str(contents).map_err(WaveLoadError::Parse)?;
+        wave_set.validate()?;
+        Ok(wave_set)
+    }
+
+    pub fn get_wave(&self, wave_level: u32) -> Option<&WaveDefinition> {
+        if wave_level == 0 {
+            return None;
+        }
+        self.waves.get((wave_level - 1) as usize)
+    }
+
+    fn validate(&self) -> Result<(), WaveLoadError> {
+        for (i, wave) in self.waves.iter().enumerate() {
+            let wave_number = i + 1;
+            if wave.groups.is_empty() {
+                return Err(WaveLoadError::Invalid(wave_number, 0, "wave has no groups".to_string()));
+            }
+            WaveSet::validate_modifiers(wave_number, &wave.modifiers)?;
+            for (j, group) in wave.groups.iter().enumerate() {
+                WaveSet::validate_group(wave_number, j + 1, group)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_modifiers(wave_number: usize, modifiers: &WaveModifiers) -> Result<(), WaveLoadError> {
+        let values = [("speed", modifiers.speed), ("health", modifiers.health), ("fire_rate", modifiers.fire_rate)];
+        for &(name, value) in values.iter() {
+            if !(value.is_finite() && value > 0.0 && value <= MAX_MODIFIER) {
+                return Err(WaveLoadError::Invalid(wave_number, 0, format!("modifier {} must be greater than 0 and at most {}", name, MAX_MODIFIER)));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_group(wave_number: usize, group_number: usize, group: &SpawnGroup) -> Result<(), WaveLoadError> {
+        let invalid = |message: String| Err(WaveLoadError::Invalid(wave_number, group_number, message));
+        //Fragments only come from a destroyed Splitter
+        if group.enemy == EnemyType::SplitterFragment {
+            return invalid("SplitterFragment can't be spawned directly".to_string());
+        }
+        if group.count == 0 || group.count > MAX_GROUP_COUNT {
+            return invalid(format!("count must be between 1 and {}", MAX_GROUP_COUNT));
+        }
+        if group.spawn_delay_ms > MAX_SPAWN_DELAY_MS || group.start_delay_ms > MAX_SPAWN_DELAY_MS {
+            return invalid(format!("delays must not exceed {}ms", MAX_SPAWN_DELAY_MS));
+        }
+        if let Some(position) = group.position {
+            if !(position >= 0.0 && position <= 1.0) {
+                return invalid("position must be between 0.0 and 1.0".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(modifiers: &str, group: &str) -> String {
+        format!("[[waves]]\n{}\n[[waves.groups]]\n{}\n", modifiers, group)
+    }
+
+    fn assert_invalid(contents: &str, wave: usize, group: usize) {
+        match WaveSet::parse(contents) {
+            Err(WaveLoadError::Invalid(invalid_wave, invalid_group, _)) => assert_eq!((invalid_wave, invalid_group), (wave, group)),
+            Err(e) => panic!("expected wave {} group {} to be invalid, got {}", wave, group, e),
+            Ok(_) => panic!("expected wave {} group {} to be invalid", wave, group)
+        }
+    }
+
+    #[test]
+    fn valid_waves_load() {
+        let contents = wave("[waves.modifiers]\nspeed = 1.5", "enemy = \"Tank\"\ncount = 200\nspawn_delay_ms = 60000\nposition = 1.0");
+        let wave_set = WaveSet::parse(&contents).unwrap();
+        assert_eq!(wave_set.waves.len(), 1);
+        assert_eq!(wave_set.waves[0].modifiers.speed, 1.5);
+        assert_eq!(wave_set.waves[0].groups[0].count, 200);
+    }
+
+    #[test]
+    fn waves_without_groups_are_rejected() {
+        assert_invalid("[[waves]]\ngroups = []\n", 1, 0);
+    }
+
+    #[test]
+    fn modifiers_out_of_range_are_rejected() {
+        let group = "enemy = \"NormalDrone\"\ncount = 1";
+        assert_invalid(&wave("[waves.modifiers]\nspeed = 10.5", group), 1, 0);
+        assert_invalid(&wave("[waves.modifiers]\nhealth = 0.0", group), 1, 0);
+        assert_invalid(&wave("[waves.modifiers]\nfire_rate = -1.0", group), 1, 0);
+        assert_invalid(&wave("[waves.modifiers]\nspeed = inf", group), 1, 0);
+        assert_invalid(&wave("[waves.modifiers]\nspeed = nan", group), 1, 0);
+    }
+
+    #[test]
+    fn groups_out_of_range_are_rejected() {
+        let valid = wave("", "enemy = \"NormalDrone\"\ncount = 1");
+        assert_invalid(&format!("{}{}", valid, wave("", "enemy = \"NormalDrone\"\ncount = 201")), 2, 1);
+        assert_invalid(&wave("", "enemy = \"NormalDrone\"\ncount = 0"), 1, 1);
+        assert_invalid(&wave("", "enemy = \"NormalDrone\"\ncount = 1\nspawn_delay_ms = 60001"), 1, 1);
+        assert_invalid(&wave("", "enemy = \"NormalDrone\"\ncount = 1\nstart_delay_ms = 60001"), 1, 1);
+        assert_invalid(&wave("", "enemy = \"NormalDrone\"\ncount = 1\nposition = 1.5"), 1, 1);
+    }
+
+    #[test]
+    fn splitter_fragments_are_rejected() {
+        assert_invalid(&wave("", "enemy = \"SplitterFragment\"\ncount = 1"), 1, 1);
+    }
+
+    #[test]
+    fn unknown_enemies_fail_to_parse() {
+        match WaveSet::parse(&wave("", "enemy = \"Dreadnought\"\ncount = 1")) {
+            Err(WaveLoadError::Parse(_)) => (),
+            _ => panic!("expected an unknown enemy to fail parsing")
+        }
+    }
+}
@@ -3,76 +3,120 @@ This work is available under the "MIT License”.
 Please see the file LICENSE in this distribution
 for license terms. */
 
-use std::time::{Instant, Duration};
 use nalgebra::Vector2;
-use rand::{Rng, thread_rng};
+use rand::{Rng, XorShiftRng};
 
 use super::entity_manager::*;
 use super::enemy::*;
+use super::boss::*;
 use super::play_space::*;
-use super::game_event_handler::GameState;
+use super::simulation::ms_to_ticks;
+use super::wave_definition::*;
+use super::game_state::GameState;
+
+const PROCEDURAL_BASE_DELAY_MS: u64 = 1000;
+const PROCEDURAL_DELAY_STEP_MS: u64 = 100;
+const PROCEDURAL_MIN_DELAY_MS: u64 = 250;
+const FORMATION_SPACING: f32 = 0.12;
+const BOSS_WAVE_INTERVAL: u32 = 5;
+const BOSS_SPAWN_OFFSET: f32 = 150.0;
+const BOSS_TARGET_X: f32 = 0.78;
+
+struct WaveSpawn {
+    enemy_type: EnemyType,
+    delay: u64,
+    position: Option<f32>
+}
 
 struct Wave {
-    pub spawn_rate: u32,
-    pub spawn_delay_ms: u64,
-    pub remaining_enemies: Vec<EnemyType>
+    modifiers: WaveModifiers,
+    remaining_spawns: Vec<WaveSpawn>,
+    boss_encounter: Option<u32>
 }
 
 pub struct WaveManager {
     progress_wave: bool,
     spawn_origin: Vector2<f32>,
     spawn_range: f32,
+    boss_target_x: f32,
+    wave_set: WaveSet,
     current_wave_level: u32,
     current_wave: Wave,
-    last_spawn: Instant
+    last_spawn: u64
 }
 
 impl WaveManager {
-    pub fn new(play_space: PlaySpace) -> WaveManager {
+    pub fn new(play_space: PlaySpace, wave_set: WaveSet) -> WaveManager {
+        let first_wave = WaveManager::create_wave(&wave_set, 1);
         WaveManager {
             progress_wave: false,
             spawn_origin: Vector2::new(play_space.player_area.w + 20.0, play_space.player_area.h / 2.0),
             spawn_range: play_space.player_area.h - 100.0,
+            boss_target_x: play_space.player_area.w * BOSS_TARGET_X,
+            wave_set,
             current_wave_level: 1,
-            current_wave: WaveManager::create_wave(1),
-            last_spawn: Instant::now()
+            current_wave: first_wave,
+            last_spawn: 0
         }
     }
 
-    fn get_random_spawn_point(&self) -> Vector2<f32> {
-        let rand_rang: f32 = thread_rng().gen();
+    fn get_spawn_point(&self, position: Option<f32>, rng: &mut XorShiftRng) -> Vector2<f32> {
+        let rand_rang: f32 = match position {
+            Some(position) => position,
+            None => rng.gen()
+        };
         Vector2::new(self.spawn_origin.x - 25.0, self.spawn_origin.y + self.spawn_range*(rand_rang - 0.5))
     }
 
-    fn spawn(&mut self, game_state: &mut GameState) {
-        for _i in 0..(self.current_wave.spawn_rate) {
-            if let Some(enemy_type) = self.current_wave.remaining_enemies.pop() {
-                let spawn_point = self.get_random_spawn_point();
-                if let Some(enemy) = Enemy::create_enemy_by_key(enemy_type, spawn_point.x, spawn_point.y) {
-                    EntityManager::add_enemy(game_state, enemy);
-                }
+    fn spawn(&mut self, game_state: &mut GameState, wave_spawn: WaveSpawn) {
+        let spawn_point = self.get_spawn_point(wave_spawn.position, &mut game_state.rng);
+        if let Some(mut enemy) = Enemy::create_enemy_by_key(wave_spawn.enemy_type, spawn_point.x, spawn_point.y) {
+            enemy.apply_modifiers(&self.current_wave.modifiers);
+            if let Some(weapon) = enemy.get_weapon_mut() {
+                let fire_delay = weapon.get_fire_delay();
+                weapon.add_cooldown(game_state.rng.gen_range(0, fire_delay + 1));
             }
+            EntityManager::add_enemy(game_state, enemy);
         }
     }
 
-    fn update_wave_level(&mut self) {
+    fn spawn_boss(&mut self, game_state: &mut GameState, encounter: u32) {
+        let boss = Boss::new(
+            self.spawn_origin.x + BOSS_SPAWN_OFFSET,
+            self.spawn_origin.y,
+            self.boss_target_x,
+            encounter
+        );
+        EntityManager::add_boss(game_state, boss);
+    }
+
+    fn update_wave_level(&mut self, tick: u64) {
         if self.wave_spawn_complete() && self.progress_wave {
             self.current_wave_level += 1; 
-            self.current_wave = WaveManager::create_wave(self.current_wave_level);
+            self.current_wave = WaveManager::create_wave(&self.wave_set, self.current_wave_level);
             self.progress_wave = false;
+            self.last_spawn = tick;
         }
     }
 
     pub fn update(&mut self, game_state: &mut GameState) {
-        self.update_wave_level();
-        if self.last_spawn.elapsed() > Duration::from_millis(self.current_wave.spawn_delay_ms) {
-            self.spawn(game_state);
-            self.last_spawn = Instant::now();
+        self.update_wave_level(game_state.tick);
+        if let Some(encounter) = self.current_wave.boss_encounter.take() {
+            self.spawn_boss(game_state, encounter);
+        }
+        while let Some(delay) = self.current_wave.remaining_spawns.last().map(|wave_spawn| wave_spawn.delay) {
+            if game_state.tick - self.last_spawn < delay {
+                break;
+            }
+            if let Some(wave_spawn) = self.current_wave.remaining_spawns.pop() {
+                self.spawn(game_state, wave_spawn);
+                self.last_spawn = game_state.tick;
+            }
         }
     }
 
     pub fn wave_spawn_complete(&self) -> bool {
-        self.current_wave.remaining_enemies.len() == 0
+        self.current_wave.remaining_spawns.len() == 0 && self.current_wave.boss_encounter.is_none()
     }
 
     pub fn set_to_progress_level(&mut self) {
@@ -83,15 +127,115 @@ impl WaveManager {
         self.current_wave_level
     }
 
-    fn create_wave(wave_level: u32) -> Wave {
-        let mut enemies: Vec<EnemyType> = Vec::new();
-        for _i in 0..(wave_level*3) {
-            enemies.push(EnemyType::NormalDrone)
+    fn pick_enemy_type(wave_level: u32, i: u32) -> EnemyType {
+        if wave_level >= 6 && i % 7 == 0 {
+            EnemyType::Tank
+        } else if wave_level >= 5 && i % 6 == 0 {
+            EnemyType::Splitter
+        } else if wave_level >= 4 && i % 5 == 0 {
+            EnemyType::SpreadDrone
+        } else if wave_level >= 3 && i % 4 == 0 {
+            EnemyType::Kamikaze
+        } else if wave_level >= 2 && i % 3 == 0 {
+            EnemyType::GunnerDrone
+        } else if wave_level >= 2 && i % 2 == 0 {
+            EnemyType::Weaver
+        } else {
+            EnemyType::NormalDrone
+        }
+    }
+
+    fn create_procedural_wave(wave_level: u32, authored_waves: u32) -> WaveDefinition {
+        let spawn_delay_ms = PROCEDURAL_BASE_DELAY_MS
+            .saturating_sub(wave_level as u64 * PROCEDURAL_DELAY_STEP_MS)
+            .max(PROCEDURAL_MIN_DELAY_MS);
+        let extra_levels = wave_level.saturating_sub(authored_waves) as f32;
+        WaveDefinition {
+            modifiers: WaveModifiers {
+                speed: (1.0 + extra_levels * 0.03).min(MAX_MODIFIER),
+                health: (1.0 + extra_levels * 0.1).min(MAX_MODIFIER),
+                fire_rate: (1.0 + extra_levels * 0.05).min(MAX_MODIFIER)
+            },
+            groups: (0..(wave_level*3)).map(|i| SpawnGroup {
+                enemy: WaveManager::pick_enemy_type(wave_level, i),
+                count: 1,
+                start_delay_ms: spawn_delay_ms,
+                spawn_delay_ms,
+                formation: Formation::Random,
+                position: None
+            }).collect()
+        }
+    }
+
+    fn get_formation_position(group: &SpawnGroup, i: u32) -> Option<f32> {
+        let center = group.position.unwrap_or(0.5);
+        let position = match group.formation {
+            Formation::Random => return group.position,
+            Formation::Column => center,
+            Formation::Line => {
+                if group.count == 1 {
+                    center
+                } else {
+                    0.1 + 0.8 * (i as f32 / (group.count - 1) as f32)
+                }
+            },
+            Formation::Vee => {
+                let rank = ((i + 1) / 2) as f32;
+                let side = if i % 2 == 0 { -1.0 } else { 1.0 };
+                center + side * rank * FORMATION_SPACING
+            }
+        };
+        Some(position.max(0.0).min(1.0))
+    }
+
+    fn get_formation_delay(group: &SpawnGroup, i: u32) -> u64 {
+        if i == 0 {
+            return ms_to_ticks(group.start_delay_ms);
+        }
+        match group.formation {
+            Formation::Line => 0,
+            Formation::Vee => if i % 2 == 0 { 0 } else { ms_to_ticks(group.spawn_delay_ms) },
+            _ => ms_to_ticks(group.spawn_delay_ms)
+        }
+    }
+
+    fn is_boss_level(wave_level: u32) -> bool {
+        wave_level % BOSS_WAVE_INTERVAL == 0
+    }
+
+    fn create_boss_wave(wave_level: u32) -> Wave {
+        Wave {
+            modifiers: WaveModifiers::default(),
+            remaining_spawns: vec![],
+            boss_encounter: Some(wave_level / BOSS_WAVE_INTERVAL)
+        }
+    }
+
+    fn create_wave(wave_set: &WaveSet, wave_level: u32) -> Wave {
+        if WaveManager::is_boss_level(wave_level) {
+            return WaveManager::create_boss_wave(wave_level);
+        }
+        //Boss levels are played in between the authored waves, not in place of them
+        let normal_level = wave_level - wave_level / BOSS_WAVE_INTERVAL;
+        let definition = match wave_set.get_wave(normal_level) {
+            Some(definition) => definition.clone(),
+            None => WaveManager::create_procedural_wave(normal_level, wave_set.waves.len() as u32)
+        };
+        let mut spawns: Vec<WaveSpawn> = Vec::new();
+        for group in &definition.groups {
+            for i in 0..group.count {
+                spawns.push(WaveSpawn {
+                    enemy_type: group.enemy,
+                    delay: WaveManager::get_formation_delay(group, i),
+                    position: WaveManager::get_formation_position(group, i)
+                });
+            }
         }
+        spawns.reverse();
         Wave {
-            spawn_rate: 1,
-            spawn_delay_ms: 1000 - (wave_level as u64 * 100),
-            remaining_enemies: enemies
+            modifiers: definition.modifiers,
+            remaining_spawns: spawns,
+            boss_encounter: None
         }
     }
-}
\ No newline at end of file
+}